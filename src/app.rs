@@ -1,10 +1,12 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use futures::StreamExt;
+use std::sync::Arc;
 
 // src/app.rs - Updated to be interface-agnostic
 use crate::{
     config::get_config,
-    providers::ProviderManager,
-    services::{execution::ExecutionService, usage, directory_autocomplete::DirectoryAutocomplete},
+    providers::{applications, ParsedQuery, ProviderBatch, ProviderManager, ScoredResult},
+    services::{arg_history::ArgHistory, icons, ExecutionService, usage, directory_autocomplete::DirectoryAutocomplete},
     types::{ActionResult, AppResult, SearchMessage}, 
     utils,
 };
@@ -15,21 +17,87 @@ pub enum FocusState {
     Results,
 }
 
+/// Extra arguments being typed for a launch result, opened with Shift+Enter
+pub struct ArgumentEntry {
+    /// The selected result; arguments go to its `argument_target()`
+    pub result: ActionResult,
+    pub input: String,
+    /// Earlier argument strings for the result that extend `input`, most recent first
+    pub completions: Vec<String>,
+    /// Highlighted completion, if the user moved into the list
+    pub selected: Option<usize>,
+    history: ArgHistory,
+}
+
+impl ArgumentEntry {
+    fn new(result: ActionResult) -> Self {
+        let mut entry = Self {
+            result,
+            input: String::new(),
+            completions: Vec::new(),
+            selected: None,
+            history: ArgHistory::load(),
+        };
+        entry.refresh_completions();
+        entry
+    }
+
+    fn refresh_completions(&mut self) {
+        self.completions = self.history.completions(&self.result.id, &self.input);
+        self.selected = None;
+    }
+
+    /// Move the highlight through the completions; moving up past the first one
+    /// returns to the typed text
+    fn select(&mut self, direction: i32) {
+        self.selected = match (self.selected, direction) {
+            (None, d) if d > 0 && !self.completions.is_empty() => Some(0),
+            (Some(0), d) if d < 0 => None,
+            (Some(i), d) if d < 0 => Some(i - 1),
+            (Some(i), _) => Some((i + 1).min(self.completions.len() - 1)),
+            (None, _) => None,
+        };
+    }
+
+    /// The argument string that Enter would launch with
+    fn chosen(&self) -> String {
+        self.selected
+            .and_then(|i| self.completions.get(i))
+            .cloned()
+            .unwrap_or_else(|| self.input.clone())
+    }
+}
+
 pub struct App {
     // UI State
     pub input: String,
     pub results: Vec<ActionResult>,
     pub selected_index: usize,
     pub focus: FocusState,
-    pub is_loading: bool,
+    /// Providers still working on the current query, in the order they were started
+    pub loading_providers: Vec<String>,
     pub error_message: Option<String>,
 
+    // Ranked results behind `results` while a streamed search is merging in
+    ranked_results: Vec<ScoredResult>,
+    // Set when a new search starts; the first batch replaces the previous results
+    pending_reset: bool,
+    // Bumped for every new query; messages tagged with an older generation are stale
+    search_generation: u64,
+    // Task running the current search, aborted when a newer query supersedes it
+    search_task: Option<tokio::task::AbortHandle>,
+    /// Result list and selection to return to while secondary actions are shown,
+    /// along with the title of the result whose actions they are
+    pub parent_results: Option<(Vec<ActionResult>, usize, String)>,
+    /// Set while arguments are being typed for the selected result
+    pub arg_entry: Option<ArgumentEntry>,
+
     // History
     pub history: Vec<String>,
     pub history_index: Option<usize>,
 
     // Services
-    pub provider_manager: ProviderManager,
+    pub provider_manager: Arc<ProviderManager>,
     pub execution_service: ExecutionService,
     directory_autocomplete: DirectoryAutocomplete,
 
@@ -42,21 +110,29 @@ impl App {
         let config = get_config();
         let mut provider_manager = ProviderManager::default();
         provider_manager.configure_all(config);
+        provider_manager.migrate_usage_ids();
+        let provider_manager = Arc::new(provider_manager);
 
-        let execution_service = ExecutionService::new();
+        let execution_service = ExecutionService::new(Arc::clone(&provider_manager));
 
         let mut app = Self {
             input: String::new(),
             results: Vec::new(),
             selected_index: 0,
             focus: FocusState::Input,
-            is_loading: false,
+            loading_providers: Vec::new(),
             error_message: None,
+            ranked_results: Vec::new(),
+            pending_reset: false,
+            search_generation: 0,
+            search_task: None,
+            parent_results: None,
+            arg_entry: None,
             history: Vec::new(),
             history_index: None,
             provider_manager,
             execution_service,
-            directory_autocomplete: DirectoryAutocomplete::new(),
+            directory_autocomplete: DirectoryAutocomplete::from_config(&get_config().providers.directories),
             should_exit: false,
         };
 
@@ -73,7 +149,7 @@ impl App {
         // Use the applications provider to get top used apps
         let apps_provider = self.provider_manager.get_provider("applications");
         if let Some(provider) = apps_provider {
-            match provider.search("").await {  // Empty query triggers top apps
+            match provider.search(&ParsedQuery::unscoped("")).await {  // Empty query triggers top apps
                 Ok(scored_results) => {
                     if !scored_results.is_empty() {
                         self.results = scored_results.into_iter()
@@ -113,7 +189,7 @@ impl App {
         self.results = common_apps.into_iter()
             .map(|(name, title, command, terminal)| {
                 ActionResult::new_launch(
-                    utils::generate_id("app", name),
+                    applications::app_id(&format!("{}.desktop", name)),
                     "applications",
                     title.to_string(),
                     command.to_string(),
@@ -125,32 +201,20 @@ impl App {
         utils::log_info(&format!("Loaded {} fallback apps", self.results.len()));
     }
 
-    // Public method for other interfaces to perform searches
-    pub async fn search(&mut self, query: &str) -> Vec<ActionResult> {
-        let scored_results = self.provider_manager.search_all(query).await;
-        scored_results.into_iter().map(|sr| sr.result).collect()
-    }
-
     // Helper method to check if query is AI-related
     fn is_ai_query(&self, query: &str) -> bool {
-        let config = get_config();
-        query.starts_with(&config.search.ai_prefix) || query.starts_with("ask:")
-    }
-
-    // Public getter for should_exit
-    pub fn should_exit(&self) -> bool {
-        self.should_exit
+        self.provider_manager.route(query).is_scoped_to("ai")
     }
 
-    // Public setter for should_exit
-    pub fn set_should_exit(&mut self, should_exit: bool) {
-        self.should_exit = should_exit;
+    /// Whether any provider is still working on the current query
+    pub fn is_loading(&self) -> bool {
+        !self.loading_providers.is_empty()
     }
 
     // TUI-specific run method (kept for TUI interface)
     pub async fn run(
         &mut self,
-        terminal: &mut ratatui::Terminal<ratatui::backend::CrosstermBackend<std::io::Stdout>>,
+        terminal: &mut ratatui::Terminal<ratatui::backend::CrosstermBackend<std::io::Stderr>>,
         search_tx: tokio::sync::mpsc::Sender<SearchMessage>,
         mut search_rx: tokio::sync::mpsc::Receiver<SearchMessage>,
     ) -> AppResult<()> {
@@ -188,9 +252,17 @@ impl App {
         event: KeyEvent,
         search_tx: &tokio::sync::mpsc::Sender<SearchMessage>,
     ) -> AppResult<()> {
+        if self.arg_entry.is_some() {
+            return self.handle_argument_key(event).await;
+        }
+
         match event.code {
             KeyCode::Esc => self.should_exit = true,
 
+            KeyCode::Enter if event.modifiers.contains(KeyModifiers::SHIFT) && self.focus == FocusState::Results => {
+                self.start_argument_entry();
+            }
+
             KeyCode::Enter => match self.focus {
                 FocusState::Input => {
                     self.handle_input_enter(search_tx).await?;
@@ -214,66 +286,71 @@ impl App {
                 FocusState::Results => self.navigate_results(1),
             },
 
-            KeyCode::Char(c) => {
-                if self.focus == FocusState::Input {
-                    self.input.push(c);
-                    self.history_index = None;
-                    match self.is_ai_query(&self.input) {
-                        true => {
-                            // AI query - show loading state
-                            self.is_loading = true;
-                            self.error_message = None;
-                        }
-                        false => {
-                            // Check for directory autocomplete first
-                            if self.input.starts_with('/') || self.input.starts_with("~/") || self.input.contains('/') {
-                                // Show directory completions
-                                let completions = self.directory_autocomplete.get_completions(&self.input);
-                                if !completions.is_empty() {
-                                    self.results = completions;
-                                    self.selected_index = 0;
-                                    return Ok(());
-                                }
-                            }
-                            
-                            // Only trigger live search for NON-AI queries
-                            if get_config().search.enable_live_search {
-                                let input = self.input.clone();
-                                self.perform_search(&input, search_tx).await;
-                            }
-                        }
-                    }
-                    
-                }
-            }
+            KeyCode::Right if self.focus == FocusState::Results => self.open_secondary_actions().await,
 
-            KeyCode::Backspace => {
-                if self.focus == FocusState::Input && !self.input.is_empty() {
-                    self.input.pop();
-                    self.history_index = None;
-
-                    if self.input.is_empty() {
-                        // When input becomes empty, show top apps again
-                        self.clear_search_state();
-                        self.load_initial_results().await;
-                    } else {
-                        // Check for directory autocomplete
+            KeyCode::Left if self.focus == FocusState::Results => self.close_secondary_actions(),
+
+            KeyCode::Char(c) if self.focus == FocusState::Input => {
+                self.parent_results = None;
+                self.input.push(c);
+                self.history_index = None;
+                match self.is_ai_query(&self.input) {
+                    true => {
+                        // AI query - show loading state until the user submits it
+                        self.cancel_search();
+                        self.loading_providers = vec!["ai".to_string()];
+                        self.error_message = None;
+                    }
+                    false => {
+                        // Check for directory autocomplete first
                         if self.input.starts_with('/') || self.input.starts_with("~/") || self.input.contains('/') {
+                            // Show directory completions
                             let completions = self.directory_autocomplete.get_completions(&self.input);
                             if !completions.is_empty() {
+                                self.cancel_search();
                                 self.results = completions;
                                 self.selected_index = 0;
                                 return Ok(());
                             }
                         }
                         
-                        // Regular live search for NON-AI queries
-                        if get_config().search.enable_live_search && !self.is_ai_query(&self.input) {
+                        // Only trigger live search for NON-AI queries
+                        if get_config().search.enable_live_search {
                             let input = self.input.clone();
                             self.perform_search(&input, search_tx).await;
                         }
                     }
                 }
+                
+            }
+
+            KeyCode::Backspace if self.focus == FocusState::Input && !self.input.is_empty() => {
+                self.parent_results = None;
+                self.input.pop();
+                self.history_index = None;
+
+                if self.input.is_empty() {
+                    // When input becomes empty, show top apps again
+                    self.clear_search_state();
+                    self.load_initial_results().await;
+                } else {
+                    // Check for directory autocomplete
+                    if self.input.starts_with('/') || self.input.starts_with("~/") || self.input.contains('/') {
+                        let completions = self.directory_autocomplete.get_completions(&self.input);
+                        if !completions.is_empty() {
+                            self.cancel_search();
+                            self.results = completions;
+                            self.selected_index = 0;
+                            return Ok(());
+                        }
+                    }
+                    
+                    // Regular live search for NON-AI queries
+                    if get_config().search.enable_live_search && !self.is_ai_query(&self.input) {
+                        let input = self.input.clone();
+                        self.perform_search(&input, search_tx).await;
+                    }
+                }
             }
 
             _ => {}
@@ -283,9 +360,12 @@ impl App {
     }
 
     fn clear_search_state(&mut self) {
-        self.is_loading = false;
+        self.cancel_search();
+        self.loading_providers.clear();
         self.error_message = None;
         self.results.clear();
+        self.ranked_results.clear();
+        self.pending_reset = false;
         self.selected_index = 0;
     }
 
@@ -315,69 +395,208 @@ impl App {
     }
 
     async fn handle_result_selection(&mut self) -> AppResult<()> {
-        if let Some(result) = self.results.get(self.selected_index) {
-            // Record usage
-            usage::record_usage(&result.id);
-
-            // Execute the action
-            match self.execution_service.execute(result).await {
-                Ok(should_exit) => {
-                    if should_exit {
-                        self.should_exit = true;
-                    } else {
-                        // Clear results and return to input
-                        self.results.clear();
-                        self.focus = FocusState::Input;
-                        self.selected_index = 0;
-                        self.error_message = None;
-                    }
-                }
-                Err(e) => {
-                    self.error_message = Some(format!("Execution failed: {}", e));
-                    self.focus = FocusState::Input;
+        if let Some(result) = self.results.get(self.selected_index).cloned() {
+            self.execute_result(&result).await;
+        }
+
+        Ok(())
+    }
+
+    /// Start typing extra arguments for the selected result, if it launches something
+    fn start_argument_entry(&mut self) {
+        let Some(selected) = self.results.get(self.selected_index).filter(|r| r.argument_target().is_some()) else {
+            return;
+        };
+        let selected = selected.clone();
+
+        // Results still streaming in would move the selection underneath us
+        self.cancel_search();
+        self.arg_entry = Some(ArgumentEntry::new(selected));
+    }
+
+    async fn handle_argument_key(&mut self, event: KeyEvent) -> AppResult<()> {
+        let Some(entry) = self.arg_entry.as_mut() else {
+            return Ok(());
+        };
+
+        match event.code {
+            KeyCode::Esc => self.arg_entry = None,
+            KeyCode::Enter => self.launch_with_arguments().await,
+            KeyCode::Up => entry.select(-1),
+            KeyCode::Down => entry.select(1),
+            KeyCode::Tab => {
+                if let Some(completion) = entry.selected.or((!entry.completions.is_empty()).then_some(0)) {
+                    entry.input = entry.completions[completion].clone();
+                    entry.refresh_completions();
                 }
             }
+            KeyCode::Char(c) => {
+                entry.input.push(c);
+                entry.refresh_completions();
+            }
+            KeyCode::Backspace => {
+                entry.input.pop();
+                entry.refresh_completions();
+            }
+            _ => {}
         }
 
         Ok(())
     }
 
-    async fn perform_search(&mut self, query: &str, search_tx: &tokio::sync::mpsc::Sender<SearchMessage>) {
-        self.is_loading = true;
-        self.error_message = None;
+    /// Launch the result of the argument entry with the typed arguments appended
+    async fn launch_with_arguments(&mut self) {
+        let Some(mut entry) = self.arg_entry.take() else {
+            return;
+        };
+        let Some(target) = entry.result.argument_target() else {
+            return;
+        };
 
-        let search_tx = search_tx.clone();
-        let query = query.to_string();
-        let provider_manager = self.provider_manager.clone();
+        let args = entry.chosen();
+        let launch = target.clone().with_arguments(&utils::shell_split(&args));
+        entry.history.record(&entry.result.id, &args);
+        if let Err(e) = entry.history.save() {
+            utils::log_error(&format!("Failed to save argument history: {}", e));
+        }
 
-        tokio::spawn(async move {
-            let results = provider_manager.search_all(&query).await;
+        self.execute_result(&launch).await;
+    }
 
-            let message = if results.is_empty() {
-                SearchMessage::Results(Vec::new())
-            } else {
-                SearchMessage::Results(results)
-            };
+    /// Record usage of `result` and run it, leaving the app or returning to the input
+    async fn execute_result(&mut self, result: &ActionResult) {
+        usage::record_usage(&result.id);
 
-            let _ = search_tx.send(message).await;
+        match self.execution_service.execute(result).await {
+            Ok(should_exit) => {
+                if should_exit {
+                    self.should_exit = true;
+                } else {
+                    // Clear results and return to input
+                    self.results.clear();
+                    self.focus = FocusState::Input;
+                    self.selected_index = 0;
+                    self.error_message = None;
+                }
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Execution failed: {}", e));
+                self.focus = FocusState::Input;
+            }
+        }
+    }
+
+    async fn perform_search(&mut self, query: &str, search_tx: &tokio::sync::mpsc::Sender<SearchMessage>) {
+        let generation = self.cancel_search();
+
+        let query = self.provider_manager.route(query);
+        self.loading_providers = self.provider_manager.handling_providers(&query);
+        self.pending_reset = true;
+        self.error_message = None;
+
+        if self.loading_providers.is_empty() {
+            self.apply_ranked_results(Vec::new());
+            return;
+        }
+
+        let search_tx = search_tx.clone();
+        let provider_manager = Arc::clone(&self.provider_manager);
+
+        let task = tokio::spawn(async move {
+            // Forward each provider's batch as soon as it's ready
+            let mut batches = provider_manager.search_stream(&query);
+            while let Some(batch) = batches.next().await {
+                let message = SearchMessage::Batch { generation, batch };
+                if search_tx.send(message).await.is_err() {
+                    break;
+                }
+            }
+            icons::save_cache();
         });
+        self.search_task = Some(task.abort_handle());
+    }
+
+    /// Abort the in-flight search, if any, and start a new generation so anything it
+    /// already queued is ignored. Aborting drops the provider futures, which cancels
+    /// their pending HTTP requests and kills spawned commands.
+    fn cancel_search(&mut self) -> u64 {
+        if let Some(task) = self.search_task.take() {
+            task.abort();
+        }
+        self.loading_providers.clear();
+        self.search_generation += 1;
+        self.search_generation
     }
 
     fn handle_search_message(&mut self, message: SearchMessage) {
+        let generation = message.generation();
+        if generation != self.search_generation {
+            utils::log_debug(&format!(
+                "Dropping stale search message (generation {}, current {})",
+                generation, self.search_generation
+            ));
+            return;
+        }
+
         match message {
-            SearchMessage::Results(scored_results) => {
-                self.results = scored_results.into_iter().map(|sr| sr.result).collect();
-                self.selected_index = 0;
-                self.is_loading = false;
-            }
-            SearchMessage::Error(error) => {
-                self.error_message = Some(error);
-                self.is_loading = false;
-            }
-            SearchMessage::Loading(loading) => {
-                self.is_loading = loading;
-            }
-            _ => {}
+            SearchMessage::Batch { batch, .. } => self.merge_batch(batch),
+        }
+    }
+
+    /// Merge one provider's results into the list without moving the selection
+    fn merge_batch(&mut self, batch: ProviderBatch) {
+        self.loading_providers.retain(|id| id != &batch.provider_id);
+
+        if self.pending_reset {
+            // First batch for a new query replaces whatever was shown before
+            self.apply_ranked_results(Vec::new());
+        }
+
+        let selected_id = self.results.get(self.selected_index).map(|r| r.id.clone());
+
+        let mut ranked = std::mem::take(&mut self.ranked_results);
+        self.provider_manager.merge_batch(&mut ranked, batch);
+        self.ranked_results = ranked;
+        self.results = self.ranked_results.iter().map(|sr| sr.result.clone()).collect();
+
+        // Keep the same result selected even if new results were ranked above it
+        self.selected_index = selected_id
+            .and_then(|id| self.results.iter().position(|r| r.id == id))
+            .unwrap_or_else(|| self.selected_index.min(self.results.len().saturating_sub(1)));
+    }
+
+    fn apply_ranked_results(&mut self, ranked: Vec<ScoredResult>) {
+        self.results = ranked.iter().map(|sr| sr.result.clone()).collect();
+        self.ranked_results = ranked;
+        self.pending_reset = false;
+        self.selected_index = 0;
+    }
+
+    /// Replace the list with the selected result's secondary actions, followed by
+    /// the apps that can open it if it points at a file or directory
+    async fn open_secondary_actions(&mut self) {
+        let Some(selected) = self.results.get(self.selected_index) else {
+            return;
+        };
+        let actions = self.provider_manager.secondary_actions(selected).await;
+        if actions.is_empty() {
+            return;
+        }
+        let title = selected.title.clone();
+
+        // Results still streaming in would overwrite the actions list
+        self.cancel_search();
+
+        let parent = std::mem::replace(&mut self.results, actions);
+        self.parent_results = Some((parent, self.selected_index, title));
+        self.selected_index = 0;
+    }
+
+    /// Go back from secondary actions to the list they were opened from
+    fn close_secondary_actions(&mut self) {
+        if let Some((results, selected_index, _)) = self.parent_results.take() {
+            self.results = results;
+            self.selected_index = selected_index;
         }
     }
 
@@ -449,16 +668,4 @@ impl App {
             self.history.truncate(config.general.history_limit);
         }
     }
-
-    pub fn clear_error(&mut self) {
-        self.error_message = None;
-    }
 }
-
-// Make ProviderManager cloneable for async operations
-impl Clone for ProviderManager {
-    fn clone(&self) -> Self {
-        // This is a simplified clone - in practice you'd want proper cloning
-        ProviderManager::default()
-    }
-}
\ No newline at end of file
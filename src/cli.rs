@@ -1,6 +1,6 @@
 // src/cli.rs - Updated with interface selection
 use clap::{Parser, Subcommand};
-use std::{collections::HashMap, path::{Path, PathBuf}};
+use std::{collections::HashMap, path::PathBuf};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -39,11 +39,13 @@ pub enum ProviderCommands {
     Test { name: String, query: String },
     /// Install default provider configurations
     InstallDefaults,
+    /// Check providers and external tools, exiting non-zero on hard failures
+    Doctor,
 }
 
 /// Parse CLI arguments and return the interface type to use
 /// Returns `Ok((should_exit, interface_type))` 
-pub fn handle_cli_args() -> Result<(bool, crate::interfaces::InterfaceType), anyhow::Error> {
+pub async fn handle_cli_args() -> Result<(bool, crate::interfaces::InterfaceType), anyhow::Error> {
     let cli_args = CliArgs::parse();
 
     // Handle --logs
@@ -77,6 +79,12 @@ pub fn handle_cli_args() -> Result<(bool, crate::interfaces::InterfaceType), any
             Some(specific_path) => specific_path,
             None => {
                 let config = crate::config::get_config();
+                // Show a file from before stable result IDs under the current ones
+                crate::services::usage::migrate_legacy_ids(|| {
+                    let mut provider_manager = crate::providers::ProviderManager::default();
+                    provider_manager.configure_all(config);
+                    provider_manager.legacy_ids()
+                });
                 config.paths.usage_stats_file.clone()
             }
         };
@@ -97,7 +105,7 @@ pub fn handle_cli_args() -> Result<(bool, crate::interfaces::InterfaceType), any
     
     // Handle --provider subcommands
     if let Some(provider_cmd) = cli_args.provider {
-        crate::providers::management::handle_provider_command(provider_cmd)?;
+        crate::providers::management::handle_provider_command(provider_cmd).await?;
         return Ok((true, crate::interfaces::InterfaceType::Tui)); // Exit after handling provider command
     }
 
@@ -154,7 +162,7 @@ fn display_usage_statistics(usage_file_path: &PathBuf) -> Result<(), anyhow::Err
     
     // Sort entries by usage count (descending)
     let mut sorted_entries: Vec<(&String, &UsageEntry)> = entries.iter().collect();
-    sorted_entries.sort_by(|a, b| b.1.count.cmp(&a.1.count));
+    sorted_entries.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.count));
     
     // Display header
     eprintln!("📊 Wayfindr Usage Statistics");
@@ -232,145 +240,29 @@ fn display_usage_statistics(usage_file_path: &PathBuf) -> Result<(), anyhow::Err
     Ok(())
 }
 
+/// Display names of the indexed applications, keyed by their result IDs
 fn load_application_names() -> HashMap<String, String> {
-    use std::fs;
-    use std::path::Path;
-    use std::collections::HashMap;
-    
-    let mut app_names = HashMap::new();
-    
-    // Search standard desktop application directories
-    let search_paths = [
-        "/usr/share/applications",
-        "/usr/local/share/applications",
-        "~/.local/share/applications",
-    ];
-    
-    for path_str in &search_paths {
-        let expanded_path = if path_str.starts_with("~") {
-            // Simple tilde expansion
-            if let Some(home) = std::env::var("HOME").ok() {
-                path_str.replace("~", &home)
-            } else {
-                continue;
-            }
-        } else {
-            path_str.to_string()
-        };
-        
-        let path = Path::new(&expanded_path);
-        if !path.exists() {
-            continue;
-        }
-        
-        if let Ok(entries) = fs::read_dir(path) {
-            for entry in entries.flatten() {
-                let file_path = entry.path();
-                
-                if file_path.extension().and_then(|s| s.to_str()) == Some("desktop") {
-                    if let Some((app_id, app_name)) = parse_desktop_file(&file_path) {
-                        app_names.insert(app_id, app_name);
-                    }
-                }
-            }
-        }
-    }
-    
-    app_names
-}
+    use crate::providers::applications::{self, ApplicationProvider};
+    use crate::services::app_index::AppIndex;
 
-fn parse_desktop_file(path: &Path) -> Option<(String, String)> {
-    use std::fs;
-    
-    let content = fs::read_to_string(path).ok()?;
-    let mut app_name = String::new();
-    let mut exec_command = String::new();
-    
-    let mut in_desktop_entry = false;
-    for line in content.lines() {
-        let line = line.trim();
-        
-        if line == "[Desktop Entry]" {
-            in_desktop_entry = true;
-            continue;
-        } else if line.starts_with('[') && line.ends_with(']') {
-            in_desktop_entry = false;
-            continue;
-        }
-        
-        if !in_desktop_entry || line.is_empty() || line.starts_with('#') {
-            continue;
-        }
-        
-        if let Some((key, value)) = line.split_once('=') {
-            match key {
-                "Name" => app_name = value.to_string(),
-                "Exec" => exec_command = value.to_string(),
-                _ => {}
-            }
-        }
-    }
-    
-    if !app_name.is_empty() && !exec_command.is_empty() {
-        // Generate the same ID that wayfindr would generate
-        let app_id = generate_app_id(&app_name);
-        Some((app_id, app_name))
-    } else {
-        None
-    }
-}
+    let config = crate::config::get_config();
+    let search_dirs = ApplicationProvider::search_dirs(&config.providers.applications.search_paths);
 
-fn generate_app_id(app_name: &str) -> String {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-    
-    let mut hasher = DefaultHasher::new();
-    app_name.hash(&mut hasher);
-    let hash = hasher.finish();
-    
-    format!("app_{:x}", hash)
+    AppIndex::new(&config.paths.cache_dir, search_dirs)
+        .apps()
+        .iter()
+        .map(|app| (applications::app_id(&app.desktop_id), app.name.clone()))
+        .collect()
 }
 
 fn resolve_app_name(app_id: &str, app_names: &HashMap<String, String>) -> String {
-    // First, try to find the exact app name from desktop files
     if let Some(name) = app_names.get(app_id) {
         return name.clone();
     }
-    
-    // If not found, try to make the ID more readable
-    if app_id.starts_with("app_") {
-        let hash_part = app_id.strip_prefix("app_").unwrap_or(app_id);
-        
-        // For known common apps, try to reverse-engineer the name
-        let common_apps = [
-            ("brave", "Brave Browser"),
-            ("firefox", "Firefox"),
-            ("chrome", "Google Chrome"),
-            ("chromium", "Chromium"),
-            ("code", "Visual Studio Code"),
-            ("kitty", "Kitty Terminal"),
-            ("alacritty", "Alacritty Terminal"),
-            ("nautilus", "Files (Nautilus)"),
-            ("thunar", "Thunar File Manager"),
-            ("spotify", "Spotify"),
-            ("discord", "Discord"),
-            ("slack", "Slack"),
-            ("gimp", "GIMP"),
-            ("libreoffice", "LibreOffice"),
-            ("vlc", "VLC Media Player"),
-        ];
-        
-        // Try to match against common app hashes
-        for (app_name, display_name) in &common_apps {
-            if generate_app_id(app_name) == *app_id {
-                return display_name.to_string();
-            }
-        }
-        
-        // If we can't resolve it, show a more user-friendly format
-        format!("App ({})", &hash_part[..8])
-    } else {
-        // For non-app IDs, just return as-is
-        app_id.to_string()
+
+    // Uninstalled apps still have a readable desktop file ID
+    match app_id.strip_prefix("app:") {
+        Some(desktop_id) => desktop_id.trim_end_matches(".desktop").to_string(),
+        None => app_id.to_string(),
     }
-}
\ No newline at end of file
+}
@@ -1,12 +1,15 @@
 // src/config/mod.rs
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::PathBuf;
 
 const CONFIG_DIR_NAME: &str = ".wayfindr";
 const CONFIG_FILE_NAME: &str = "config.toml";
 const DEFAULT_TERMINAL: &str = "alacritty";
+const DEFAULT_PROVIDER_TIMEOUT_MS: u64 = 1500;
+const DEFAULT_ICON_SIZE: u32 = 48;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -14,6 +17,12 @@ pub struct Config {
     pub search: SearchConfig,
     pub ui: UiConfig,
     pub paths: PathsConfig,
+    #[serde(default)]
+    pub providers: ProvidersConfig,
+    #[serde(default)]
+    pub scoring: ScoringConfig,
+    #[serde(default)]
+    pub navigate: NavigateConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +39,17 @@ pub struct SearchConfig {
     pub app_prefix: String,
     pub fuzzy_threshold: f32,
     pub enable_live_search: bool,
+    /// Timeout applied to providers without their own entry in `provider_timeouts`
+    #[serde(default = "default_provider_timeout_ms")]
+    pub provider_timeout_ms: u64,
+    /// Per-provider timeout overrides in milliseconds, keyed by provider id
+    #[serde(default)]
+    pub provider_timeouts: HashMap<String, u64>,
+    /// Prefix aliases keyed by provider id, e.g. `weather = ["w:", "weather:"]`.
+    /// An entry replaces that provider's built-in prefixes; `ai_prefix` and
+    /// `app_prefix` are the built-in defaults for the AI and application providers.
+    #[serde(default)]
+    pub prefixes: HashMap<String, Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +57,12 @@ pub struct UiConfig {
     pub show_icons: bool,
     pub show_categories: bool,
     pub animate_transitions: bool,
+    /// Icon theme for result icons; unset follows the GTK settings, then hicolor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_theme: Option<String>,
+    /// Preferred icon size in pixels
+    #[serde(default = "default_icon_size")]
+    pub icon_size: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +73,292 @@ pub struct PathsConfig {
     pub cache_dir: PathBuf,
 }
 
+/// Weights for ranking results from different providers against each other.
+/// The formula is documented on `providers::scoring::ScoreBreakdown`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringConfig {
+    /// Weight of the provider-reported relevance (0..=1)
+    pub relevance_weight: f32,
+    /// Weight of the provider priority divided by 100
+    pub provider_weight: f32,
+    /// Weight of how often the result has been chosen
+    pub usage_weight: f32,
+    /// Weight of how recently the result was chosen
+    pub recency_weight: f32,
+    /// Launch count at which the usage signal reaches its maximum
+    pub usage_saturation: u32,
+    /// Days after which the recency signal has halved
+    pub recency_half_life_days: f32,
+    /// Append each result's score breakdown to its description
+    pub debug: bool,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            relevance_weight: 0.6,
+            provider_weight: 0.15,
+            usage_weight: 0.15,
+            recency_weight: 0.1,
+            usage_saturation: 50,
+            recency_half_life_days: 7.0,
+            debug: false,
+        }
+    }
+}
+
+/// What choosing a directory does. Handlers other than the default are offered as
+/// secondary actions on directory results.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NavigateConfig {
+    /// Handler run when a directory result is chosen
+    pub default_handler: String,
+    /// Handlers by name, added to or replacing the built-in "terminal",
+    /// "file_manager", "editor" and "shell"
+    pub handlers: BTreeMap<String, NavigateHandlerConfig>,
+}
+
+impl Default for NavigateConfig {
+    fn default() -> Self {
+        Self {
+            default_handler: "terminal".to_string(),
+            handlers: BTreeMap::new(),
+        }
+    }
+}
+
+/// One way of opening a directory. `command` and `print` may use `{path}` for the
+/// shell-quoted directory, `{terminal}` for `default_terminal` opened in it, and
+/// `{terminal_exec}` for the same followed by the flag that runs a command.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NavigateHandlerConfig {
+    /// Title of the secondary action, e.g. "Open in editor"
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// Shell command run through Hyprland
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Printed to stdout once wayfindr exits instead of running a command, for
+    /// shell integration such as `eval "$(wayfindr)"`. The TUI draws on stderr, so
+    /// this works from both interfaces, but only when a shell captures stdout; when
+    /// wayfindr is started from a keybinding the text goes nowhere.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub print: Option<String>,
+}
+
+/// Settings for the built-in providers, one `[providers.<id>]` table each.
+/// Dynamic providers keep their own files under `providers/`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProvidersConfig {
+    #[serde(default)]
+    pub applications: ApplicationsProviderConfig,
+    #[serde(default)]
+    pub directories: DirectoriesProviderConfig,
+    #[serde(default)]
+    pub files: FilesProviderConfig,
+    #[serde(default)]
+    pub executables: ExecutablesProviderConfig,
+    #[serde(default)]
+    pub windows: WindowsProviderConfig,
+    #[serde(default)]
+    pub ai: AiProviderConfig,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApplicationsProviderConfig {
+    pub enabled: bool,
+    /// Overrides the provider's built-in priority
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+    /// Maximum number of matches returned for a search query
+    pub max_results: usize,
+    /// Directories scanned for `.desktop` files, later ones overriding earlier ones;
+    /// empty uses the `applications` directories of the XDG data dirs
+    pub search_paths: Vec<String>,
+}
+
+impl Default for ApplicationsProviderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            priority: None,
+            max_results: 20,
+            search_paths: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DirectoriesProviderConfig {
+    pub enabled: bool,
+    /// Overrides the provider's built-in priority
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+    /// Maximum number of directories returned for a search query
+    pub max_results: usize,
+    /// zoxide binary, looked up in `PATH` unless absolute
+    pub zoxide_path: String,
+    /// Where directory matches come from: zoxide, the built-in frecency database,
+    /// or zoxide when it is installed and the built-in database otherwise
+    pub backend: DirectoryBackend,
+    /// Databases to copy into the built-in one on the next start, once each:
+    /// "zoxide", "autojump" and "fasd"
+    pub import_from: Vec<String>,
+    /// Total rank at which the built-in database ages its entries, like `_ZO_MAXAGE`
+    pub max_age: f64,
+    /// Directories never offered, like zoxide's `--exclude`
+    pub exclude: Vec<String>,
+    /// Only offer directories below this one, like zoxide's `--base-dir`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_dir: Option<String>,
+    /// Offer files alongside directories when completing a typed path
+    pub complete_files: bool,
+    /// Directory that relative paths such as `./src` or `../notes` are completed from
+    pub relative_to: String,
+}
+
+impl Default for DirectoriesProviderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            priority: None,
+            max_results: 15,
+            zoxide_path: "zoxide".to_string(),
+            backend: DirectoryBackend::Auto,
+            import_from: Vec::new(),
+            max_age: 10_000.0,
+            exclude: Vec::new(),
+            base_dir: None,
+            complete_files: false,
+            relative_to: "~".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DirectoryBackend {
+    #[default]
+    Auto,
+    Zoxide,
+    Native,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilesProviderConfig {
+    /// Off by default, since indexing walks everything below `roots`
+    pub enabled: bool,
+    /// Overrides the provider's built-in priority
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+    /// Maximum number of files returned for a search query
+    pub max_results: usize,
+    /// Directories indexed for file search
+    pub roots: Vec<String>,
+    /// Directory levels below each root that are indexed
+    pub max_depth: usize,
+    /// Index dotfiles and files in dot-directories
+    pub include_hidden: bool,
+    /// Leave out what `.gitignore` and `.ignore` files ignore
+    pub respect_ignore_files: bool,
+    /// Seconds before the index is checked for changes again
+    pub refresh_interval_secs: u64,
+}
+
+impl Default for FilesProviderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            priority: None,
+            max_results: 10,
+            roots: vec!["~".to_string()],
+            max_depth: 6,
+            include_hidden: false,
+            respect_ignore_files: true,
+            refresh_interval_secs: 300,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecutablesProviderConfig {
+    pub enabled: bool,
+    /// Overrides the provider's built-in priority
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+    /// Maximum number of executables returned for a search query
+    pub max_results: usize,
+    /// Whether executables open in `default_terminal` unless listed in `terminal`
+    pub terminal_by_default: bool,
+    /// Per-binary overrides of `terminal_by_default`, e.g. `htop = true`
+    pub terminal: HashMap<String, bool>,
+}
+
+impl Default for ExecutablesProviderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            priority: None,
+            max_results: 10,
+            terminal_by_default: false,
+            terminal: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowsProviderConfig {
+    /// The provider also stays disabled outside a Hyprland session
+    pub enabled: bool,
+    /// Overrides the provider's built-in priority
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+    /// Maximum number of windows returned for a search query
+    pub max_results: usize,
+    /// Make application results focus an already open window instead of launching
+    /// another instance
+    pub focus_existing: bool,
+}
+
+impl Default for WindowsProviderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            priority: None,
+            max_results: 15,
+            focus_existing: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AiProviderConfig {
+    /// The provider also stays disabled while `GEMINI_API_KEY` is unset
+    pub enabled: bool,
+    /// Overrides the provider's built-in priority
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u8>,
+}
+
+impl Default for AiProviderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            priority: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum LogLevel {
@@ -72,11 +384,16 @@ impl Default for Config {
                 app_prefix: "app:".to_string(),
                 fuzzy_threshold: 0.6,
                 enable_live_search: true,
+                provider_timeout_ms: DEFAULT_PROVIDER_TIMEOUT_MS,
+                provider_timeouts: HashMap::new(),
+                prefixes: HashMap::new(),
             },
             ui: UiConfig {
                 show_icons: false,
                 show_categories: true,
                 animate_transitions: false,
+                icon_theme: None,
+                icon_size: DEFAULT_ICON_SIZE,
             },
             paths: PathsConfig {
                 config_dir: config_dir.clone(),
@@ -84,6 +401,9 @@ impl Default for Config {
                 usage_stats_file: config_dir.join("usage_stats.txt"),
                 cache_dir: config_dir.join("cache"),
             },
+            providers: ProvidersConfig::default(),
+            scoring: ScoringConfig::default(),
+            navigate: NavigateConfig::default(),
         }
     }
 }
@@ -127,6 +447,14 @@ impl Config {
     }
 }
 
+fn default_icon_size() -> u32 {
+    DEFAULT_ICON_SIZE
+}
+
+fn default_provider_timeout_ms() -> u64 {
+    DEFAULT_PROVIDER_TIMEOUT_MS
+}
+
 fn get_config_dir() -> PathBuf {
     dirs::home_dir()
         .expect("Could not find home directory")
@@ -149,8 +477,37 @@ pub fn init_config() -> Result<()> {
     Ok(())
 }
 
+/// Like `get_config`, but returns `None` instead of panicking before `init_config` runs
+pub fn try_get_config() -> Option<&'static Config> {
+    CONFIG.get()
+}
+
 pub fn get_config() -> &'static Config {
     CONFIG
         .get()
         .expect("Config not initialized. Call init_config() first.")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_tables_are_optional() {
+        let mut config = Config::default();
+        config.providers.directories.zoxide_path = "/opt/zoxide".to_string();
+        let content = toml::to_string_pretty(&config).unwrap();
+
+        // Configs written before `[providers]` existed still load with the defaults
+        let (without_providers, _) = content.split_once("[providers").unwrap();
+        let parsed: Config = toml::from_str(without_providers).unwrap();
+        assert_eq!(parsed.providers.applications.max_results, 20);
+        assert!(parsed.providers.ai.enabled);
+
+        // Partial tables fill in the remaining fields
+        let partial = format!("{}[providers.directories]\nmax_results = 5\n", without_providers);
+        let parsed: Config = toml::from_str(&partial).unwrap();
+        assert_eq!(parsed.providers.directories.max_results, 5);
+        assert_eq!(parsed.providers.directories.zoxide_path, "zoxide");
+    }
+}
@@ -1,7 +1,8 @@
 // src/interfaces/rofi.rs - Cleaned up to use system rofi theme
 use crate::{
     app::App,
-    services::usage,
+    providers::{dedup, ParsedQuery, ScoredResult},
+    services::{arg_history::ArgHistory, usage},
     types::{ActionResult, ActionType, AppResult},
     utils,
 };
@@ -14,6 +15,27 @@ pub async fn run_rofi(mut app: App) -> AppResult<()> {
     rofi.run(&mut app).await
 }
 
+/// How the user confirmed a rofi selection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RofiKey {
+    Accept,
+    SecondaryActions,
+    Arguments,
+}
+
+/// What a menu is for, which decides the extra key bindings and whether text that
+/// matches no entry is accepted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MenuMode {
+    /// The main result list: Alt+Enter opens secondary actions, Shift+Enter asks
+    /// for arguments
+    Results,
+    /// Picking one of the entries
+    Pick,
+    /// Free text, with the entries offered as suggestions
+    Prompt,
+}
+
 pub struct RofiInterface {
     config: RofiConfig,
 }
@@ -63,14 +85,40 @@ impl RofiInterface {
         }
 
         // Format results for rofi
-        let rofi_entries = self.format_results_for_rofi(&all_results);
+        let rofi_entries = self.rofi_rows(&all_results);
 
         // Execute rofi and get selection
-        let selection = self.execute_rofi(&rofi_entries).await?;
+        let message = format!("{}  (Alt+Enter: more actions, Shift+Enter: arguments)", self.config.placeholder);
+        let Some((selection, key)) = self
+            .execute_rofi_menu(&rofi_entries, &self.config.prompt, &message, MenuMode::Results)
+            .await?
+        else {
+            return Ok(());
+        };
 
         // Parse selection and execute if found
-        if let Some(selected_result) = self.parse_selection(&selection, &all_results) {
+        if let Some(selected_result) = self.parse_selection(&Some(selection), &all_results) {
             utils::log_info(&format!("Rofi selection: {} ({})", selected_result.title, selected_result.provider));
+
+            let selected_result = match key {
+                RofiKey::SecondaryActions => {
+                    let mut selected_result = selected_result;
+                    selected_result.secondary_actions = app.provider_manager.secondary_actions(&selected_result).await;
+                    if selected_result.secondary_actions.is_empty() {
+                        selected_result
+                    } else {
+                        match self.choose_secondary_action(&selected_result).await? {
+                            Some(action) => action,
+                            None => return Ok(()),
+                        }
+                    }
+                }
+                RofiKey::Arguments => match self.prompt_arguments(&selected_result).await? {
+                    Some(launch) => launch,
+                    None => return Ok(()),
+                },
+                RofiKey::Accept => selected_result,
+            };
             
             self.handle_selection(&selected_result, app).await?;
         }
@@ -78,6 +126,43 @@ impl RofiInterface {
         Ok(())
     }
 
+    /// Show a second menu with the result's secondary actions
+    async fn choose_secondary_action(&self, result: &ActionResult) -> AppResult<Option<ActionResult>> {
+        let entries = self.rofi_rows(&result.secondary_actions);
+        let message = format!("Actions for {}", result.title);
+        let selection = self
+            .execute_rofi_menu(&entries, &self.config.prompt, &message, MenuMode::Pick)
+            .await?
+            .map(|(selection, _)| selection);
+
+        Ok(self.parse_selection(&selection, &result.secondary_actions))
+    }
+
+    /// Ask for extra arguments, suggesting ones used with the result before, and
+    /// return the launch with them appended. Results that don't launch anything
+    /// are returned as they are.
+    async fn prompt_arguments(&self, result: &ActionResult) -> AppResult<Option<ActionResult>> {
+        let Some(target) = result.argument_target() else {
+            return Ok(Some(result.clone()));
+        };
+
+        let mut history = ArgHistory::load();
+        let suggestions = history.completions(&result.id, "");
+        let message = format!("Arguments for {}", result.title);
+        let Some((args, _)) = self
+            .execute_rofi_menu(&suggestions, "args", &message, MenuMode::Prompt)
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        history.record(&result.id, &args);
+        if let Err(e) = history.save() {
+            utils::log_error(&format!("Failed to save argument history: {}", e));
+        }
+        Ok(Some(target.clone().with_arguments(&utils::shell_split(&args))))
+    }
+
     fn check_rofi_available(&self) -> AppResult<()> {
         match Command::new("rofi").arg("-version").output() {
             Ok(output) if output.status.success() => {
@@ -101,7 +186,7 @@ impl RofiInterface {
 
         // Get all apps with search
         utils::log_debug("Gathering all applications...");
-        let all_apps_results = app.provider_manager.search_all("apps").await;
+        let all_apps_results = app.provider_manager.search_parsed(&ParsedQuery::scoped("applications", "")).await;
         for scored_result in all_apps_results {
             all_results.push(scored_result.result);
         }
@@ -112,7 +197,7 @@ impl RofiInterface {
         if let Some(provider) = dir_provider {
             let dir_searches = vec!["", "Documents", "Downloads", "dev", "repos", "home"];
             for search_term in dir_searches {
-                if let Ok(dir_results) = provider.search(search_term).await {
+                if let Ok(dir_results) = provider.search(&ParsedQuery::unscoped(search_term)).await {
                     for scored_result in dir_results {
                         all_results.push(scored_result.result);
                     }
@@ -172,7 +257,9 @@ impl RofiInterface {
                         tags: vec!["ai".to_string(), "helper".to_string()],
                         usage_count: 0,
                         last_used: None,
+                        desktop_id: None,
                     },
+                    secondary_actions: Vec::new(),
                 };
                 all_results.push(ai_result);
             }
@@ -200,14 +287,21 @@ impl RofiInterface {
                     tags: vec!["helper".to_string(), command.to_string()],
                     usage_count: 0,
                     last_used: None,
+                    desktop_id: None,
                 },
+                secondary_actions: Vec::new(),
             };
             all_results.push(helper_result);
         }
 
-        // Deduplicate by ID
-        let mut seen_ids = std::collections::HashSet::new();
-        all_results.retain(|result| seen_ids.insert(result.id.clone()));
+        // Deduplicate the same app or directory gathered from several sources,
+        // keeping the first copy
+        let mut scored: Vec<ScoredResult> = all_results
+            .into_iter()
+            .map(|result| ScoredResult::new(result, 0.0))
+            .collect();
+        dedup::merge_duplicates(&mut scored);
+        let mut all_results: Vec<ActionResult> = scored.into_iter().map(|sr| sr.result).collect();
 
         // Sort by provider priority and usage boost
         all_results.sort_by(|a, b| {
@@ -243,18 +337,23 @@ impl RofiInterface {
         Ok(all_results)
     }
 
+    /// Display text of each result, which is also what rofi returns on selection
     fn format_results_for_rofi(&self, results: &[ActionResult]) -> Vec<String> {
         results
             .iter()
             .map(|result| {
-                let icon = self.get_result_icon(result);
                 let provider_tag = self.get_provider_tag(&result.provider);
+                // Rows with a real icon don't need the emoji
+                let prefix = match self.row_icon(result) {
+                    Some(_) => String::new(),
+                    None => format!("{} ", self.get_result_icon(result)),
+                };
                 
                 if result.description.is_empty() || result.description == result.title {
-                    format!("{} {} [{}]", icon, result.title, provider_tag)
+                    format!("{}{} [{}]", prefix, result.title, provider_tag)
                 } else {
-                    format!("{} {} - {} [{}]", 
-                        icon, 
+                    format!("{}{} - {} [{}]", 
+                        prefix, 
                         result.title, 
                         self.truncate(&result.description, 40),
                         provider_tag
@@ -264,18 +363,73 @@ impl RofiInterface {
             .collect()
     }
 
+    /// Rows to send to rofi: the display text plus the icon as a `\0icon\x1f` row option
+    fn rofi_rows(&self, results: &[ActionResult]) -> Vec<String> {
+        self.format_results_for_rofi(results)
+            .into_iter()
+            .zip(results)
+            .map(|(text, result)| match self.row_icon(result) {
+                Some(icon) => format!("{}\0icon\x1f{}", text, icon),
+                None => text,
+            })
+            .collect()
+    }
+
+    /// Icon file path, or a theme icon name rofi can look up itself. Emoji stored as
+    /// icons by some providers are left to the text.
+    fn row_icon<'a>(&self, result: &'a ActionResult) -> Option<&'a str> {
+        if !self.config.show_icons {
+            return None;
+        }
+        result
+            .metadata
+            .icon
+            .as_deref()
+            .filter(|icon| !icon.is_empty() && icon.is_ascii())
+    }
+
     async fn execute_rofi(&self, entries: &[String]) -> AppResult<Option<String>> {
+        let selection = self
+            .execute_rofi_menu(entries, &self.config.prompt, &self.config.placeholder, MenuMode::Pick)
+            .await?;
+        Ok(selection.map(|(selection, _)| selection))
+    }
+
+    /// Run a rofi menu and return the chosen entry along with the key used to choose it
+    async fn execute_rofi_menu(
+        &self,
+        entries: &[String],
+        prompt: &str,
+        message: &str,
+        mode: MenuMode,
+    ) -> AppResult<Option<(String, RofiKey)>> {
         let mut cmd = AsyncCommand::new("rofi");
-        cmd.arg("-dmenu")
-           .arg("-i") // Case insensitive
-           .arg("-p").arg(&self.config.prompt)
-           .arg("-mesg").arg(&self.config.placeholder)
+        cmd.arg("-dmenu");
+        if !self.config.case_sensitive {
+            cmd.arg("-i");
+        }
+        cmd.arg("-p").arg(prompt)
+           .arg("-mesg").arg(message)
            .arg("-lines").arg(self.config.lines.to_string())
            .arg("-width").arg(self.config.width.to_string())
            .arg("-matching").arg("fuzzy")
-           .arg("-no-custom") // Only allow selections from the list
            .arg("-format").arg("s"); // Return the selected string
 
+        if mode != MenuMode::Prompt {
+            cmd.arg("-no-custom"); // Only allow selections from the list
+        }
+
+        if self.config.show_icons {
+            cmd.arg("-show-icons");
+        }
+
+        if mode == MenuMode::Results {
+            // Shift+Return is bound to kb-accept-alt by default and has to be freed first
+            cmd.arg("-kb-custom-1").arg("Alt+Return")
+               .arg("-kb-accept-alt").arg("")
+               .arg("-kb-custom-2").arg("Shift+Return");
+        }
+
         // Use system rofi theme (selected via 'rofi theme selector')
         // No explicit theme argument - let rofi use the user's configured theme
 
@@ -304,16 +458,20 @@ impl RofiInterface {
                 format!("Failed to read rofi output: {}", e)
             ))?;
 
-        if output.status.success() {
-            let selection = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            if selection.is_empty() {
-                Ok(None)
-            } else {
-                Ok(Some(selection))
-            }
-        } else {
+        // rofi exits with 10 + n for kb-custom-n
+        let key = match output.status.code() {
+            Some(0) => RofiKey::Accept,
+            Some(10) if mode == MenuMode::Results => RofiKey::SecondaryActions,
+            Some(11) if mode == MenuMode::Results => RofiKey::Arguments,
             // User cancelled or rofi failed
+            _ => return Ok(None),
+        };
+
+        let selection = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if selection.is_empty() {
             Ok(None)
+        } else {
+            Ok(Some((selection, key)))
         }
     }
 
@@ -344,8 +502,7 @@ impl RofiInterface {
             _ => {
                 // Handle normal actions (apps, directories, etc.)
                 usage::record_usage(&selected_result.id);
-                let execution_service = crate::services::ExecutionService::new();
-                execution_service.execute(&selected_result).await?;
+                app.execution_service.execute(selected_result).await?;
                 Ok(())
             }
         }
@@ -401,37 +558,32 @@ impl RofiInterface {
         if search_results.len() == 1 {
             let result = &search_results[0].result;
             usage::record_usage(&result.id);
-            let execution_service = crate::services::ExecutionService::new();
-            execution_service.execute(result).await?;
+            app.execution_service.execute(result).await?;
         } else {
             // Multiple results - show them in a second rofi instance
             let sub_results: Vec<ActionResult> = search_results.into_iter()
                 .map(|sr| sr.result)
                 .collect();
             
-            let sub_entries = self.format_results_for_rofi(&sub_results);
+            let sub_entries = self.rofi_rows(&sub_results);
             let sub_selection = self.execute_rofi(&sub_entries).await?;
             
             if let Some(final_result) = self.parse_selection(&sub_selection, &sub_results) {
                 usage::record_usage(&final_result.id);
-                let execution_service = crate::services::ExecutionService::new();
-                execution_service.execute(&final_result).await?;
+                app.execution_service.execute(&final_result).await?;
             }
         }
         Ok(())
     }
 
+    /// Emoji stand-in for results without an icon
     fn get_result_icon(&self, result: &ActionResult) -> &'static str {
-        // Use icon from metadata if available, otherwise fall back to action type
-        if let Some(ref icon) = result.metadata.icon {
-            // For emoji icons, return as-is (but we need to convert to &'static str)
-            // This is a limitation - we'll use the action type for now
-        }
-        
         match &result.action {
             ActionType::Launch { needs_terminal: true } => "⚡",
             ActionType::Launch { needs_terminal: false } => "🚀",
             ActionType::Navigate { .. } => "📁",
+            ActionType::Open { .. } => "📄",
+            ActionType::Focus { .. } => "🪟",
             ActionType::AiResponse => "🤖",
             ActionType::Custom { .. } => match result.provider.as_str() {
                 "ai_helper" => "🤖",
@@ -445,6 +597,9 @@ impl RofiInterface {
         match provider {
             "applications" => "APP",
             "directories" => "DIR", 
+            "files" => "FILE",
+            "executables" => "BIN",
+            "windows" => "WIN",
             "ai_helper" => "AI",
             "helper" => "CMD",
             "weather" => "WTH",
@@ -11,12 +11,15 @@ pub async fn run_tui(mut app: App) -> AppResult<()> {
     terminal::setup_terminal().map_err(|e| crate::types::AppError::Terminal(e.to_string()))?;
 
     // Create terminal and message channel
-    let backend = CrosstermBackend::new(std::io::stdout());
+    let backend = CrosstermBackend::new(std::io::stderr());
     let mut terminal = ratatui::Terminal::new(backend)
         .map_err(|e| crate::types::AppError::Terminal(e.to_string()))?;
     
     let (search_tx, search_rx) = mpsc::channel::<SearchMessage>(32);
 
+    // The TUI stays open across many searches, so keep provider data fresh
+    app.provider_manager.watch_all();
+
     // Run main TUI loop
     let result = app.run(&mut terminal, search_tx, search_rx).await;
 
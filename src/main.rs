@@ -22,9 +22,10 @@ async fn main() -> Result<()> {
 
     // Initialize services
     services::usage::init_usage_service().context("Failed to initialize usage service")?;
+    services::frecency::init_frecency_store().context("Failed to initialize frecency database")?;
 
     // Handle CLI arguments and get interface type
-    let (should_exit_early, interface_type) = cli::handle_cli_args()?;
+    let (should_exit_early, interface_type) = cli::handle_cli_args().await?;
     if should_exit_early {
         return Ok(());
     }
@@ -57,7 +58,16 @@ async fn run_application(interface_type: InterfaceType) -> Result<()> {
 
     // Run with the selected interface
     run_interface(interface_type, app).await
-        .map_err(|e| anyhow::anyhow!("Application error: {}", e))
+        .map_err(|e| anyhow::anyhow!("Application error: {}", e))?;
+
+    // Icons resolved by searches that were cancelled before they finished
+    services::icons::save_cache();
+
+    // Left by a navigate handler for the calling shell, now that the terminal is back
+    if let Some(output) = services::navigate::take_pending_output() {
+        println!("{}", output);
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -1,20 +1,29 @@
 // src/providers/ai.rs
 use crate::{
-    providers::{ScoredResult, SearchProvider},
+    providers::{ParsedQuery, ProviderHealth, ScoredResult, SearchProvider},
     services::ai::query_gemini_api,
-    types::{ActionData, ActionMetadata, ActionResult, ActionType, ProviderResult},
+    types::{ActionMetadata, ActionResult, ProviderResult},
     utils,
 };
 use async_trait::async_trait;
+use std::time::Duration;
+
+const DEFAULT_PRIORITY: u8 = 80;
 
 pub struct AiProvider {
     enabled: bool,
+    enabled_in_config: bool,
+    prefix: String,
+    priority: u8,
 }
 
 impl AiProvider {
     pub fn new() -> Self {
         Self {
             enabled: std::env::var("GEMINI_API_KEY").is_ok(),
+            enabled_in_config: true,
+            prefix: "ai:".to_string(),
+            priority: DEFAULT_PRIORITY,
         }
     }
 }
@@ -29,30 +38,31 @@ impl SearchProvider for AiProvider {
         "AI Assistant"
     }
 
-    fn can_handle(&self, query: &str) -> bool {
+    fn can_handle(&self, query: &ParsedQuery) -> bool {
         // Only handle AI queries that are explicitly prefixed
         // This prevents accidental AI calls during live search
-        self.enabled && (query.starts_with("ai:") || query.starts_with("ask:"))
+        self.enabled && query.is_scoped_to(self.id())
+    }
+
+    fn prefixes(&self) -> Vec<String> {
+        vec![self.prefix.clone(), "ask:".to_string()]
     }
 
     fn priority(&self) -> u8 {
-        80 // High priority for AI queries
+        self.priority // High priority for AI queries by default
     }
 
-    async fn search(&self, query: &str) -> ProviderResult<Vec<ScoredResult>> {
+    fn default_timeout(&self) -> Option<Duration> {
+        Some(Duration::from_secs(15)) // Model responses routinely take several seconds
+    }
+
+    async fn search(&self, query: &ParsedQuery) -> ProviderResult<Vec<ScoredResult>> {
         if !self.enabled {
             return Ok(Vec::new());
         }
 
-        // Extract the actual AI query
-        let ai_query = if query.starts_with("ai:") {
-            query.strip_prefix("ai:").unwrap_or("").trim()
-        } else if query.starts_with("ask:") {
-            query.strip_prefix("ask:").unwrap_or("").trim()
-        } else {
-            // This should not happen due to can_handle check, but be safe
-            return Ok(Vec::new());
-        };
+        // The router has already stripped the prefix
+        let ai_query = query.text.as_str();
 
         // Require non-empty query after prefix
         if ai_query.is_empty() {
@@ -71,23 +81,19 @@ impl SearchProvider for AiProvider {
                 }
 
                 let action_id = utils::generate_id("ai", ai_query);
-                let result = ActionResult {
-                    id: action_id,
-                    provider: self.id().to_string(),
-                    action: ActionType::AiResponse,
-                    title: format!("AI: {}", utils::truncate_text(ai_query, 50)),
-                    description: response.clone(),
-                    data: ActionData::Text(response),
-                    metadata: ActionMetadata {
+                let title = format!("AI: {}", utils::truncate_text(ai_query, 50));
+                let result = ActionResult::new_ai_response(action_id, title, response.clone())
+                    .with_description(response)
+                    .with_metadata(ActionMetadata {
                         icon: Some("brain".to_string()),
                         category: Some("ai".to_string()),
                         tags: vec!["ai".to_string(), "assistant".to_string()],
                         usage_count: 0,
                         last_used: None,
-                    },
-                };
+                        desktop_id: None,
+                    });
 
-                let scored_result = ScoredResult::new(result, 1000, self.id().to_string());
+                let scored_result = ScoredResult::new(result, 1.0);
                 Ok(vec![scored_result])
             }
             Err(e) => {
@@ -100,9 +106,23 @@ impl SearchProvider for AiProvider {
         }
     }
 
-    fn configure(&mut self, _config: &crate::config::Config) {
+    fn configure(&mut self, config: &crate::config::Config) {
+        let settings = &config.providers.ai;
+        self.enabled_in_config = settings.enabled;
         // Re-check if API key is available
-        self.enabled = std::env::var("GEMINI_API_KEY").is_ok();
+        self.enabled = settings.enabled && std::env::var("GEMINI_API_KEY").is_ok();
+        self.priority = settings.priority.unwrap_or(DEFAULT_PRIORITY);
+        self.prefix = config.search.ai_prefix.clone();
+    }
+
+    async fn health(&self) -> ProviderHealth {
+        if !self.enabled_in_config {
+            ProviderHealth::warning("Disabled in config")
+        } else if std::env::var("GEMINI_API_KEY").is_err() {
+            ProviderHealth::warning(format!("GEMINI_API_KEY is not set; '{}' queries are ignored", self.prefix))
+        } else {
+            ProviderHealth::ok(format!("Ready ('{}' queries)", self.prefix))
+        }
     }
 }
 
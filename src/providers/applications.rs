@@ -1,148 +1,403 @@
 // src/providers/applications.rs
 use crate::{
-    providers::{ScoredResult, SearchProvider},
-    services::usage,
+    config::get_config,
+    providers::{
+        desktop_entry::{self, DesktopFile, ExecContext},
+        windows::{self, CachedBackend, HyprctlBackend, WindowBackend},
+        ParsedQuery, ProviderHealth, ScoredResult, SearchProvider,
+    },
+    services::{app_index::AppIndex, icons, mime::{self, MimeApps}, usage},
     types::{ActionData, ActionMetadata, ActionResult, ActionType, ProviderError, ProviderResult},
     utils,
 };
 use async_trait::async_trait;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DesktopApp {
+    /// Desktop file ID, the path below the `applications` directory joined with `-`
+    pub desktop_id: String,
+    /// Where the entry was loaded from, for the `%k` field code
+    #[serde(default)]
+    pub desktop_file: Option<PathBuf>,
+    /// Name in the current locale
     pub name: String,
+    /// `Name=` without a locale, which usage IDs before stable result IDs were derived from
+    #[serde(default)]
+    pub unlocalized_name: String,
+    pub generic_name: Option<String>,
+    pub keywords: Vec<String>,
     pub exec: String,
+    /// Program that must be installed for the entry to be shown
+    pub try_exec: Option<String>,
+    /// Working directory to launch in
+    pub working_dir: Option<String>,
     pub icon: Option<String>,
     pub comment: Option<String>,
     pub categories: Vec<String>,
     pub no_display: bool,
+    /// Deleted by the user or vendor; still masks entries with the same ID in
+    /// lower-precedence directories
+    pub hidden: bool,
+    pub only_show_in: Vec<String>,
+    pub not_show_in: Vec<String>,
     pub terminal: bool,
+    /// Window class the app's windows are expected to have
+    #[serde(default)]
+    pub startup_wm_class: Option<String>,
+    /// Additional actions from `Actions=`, in declaration order
+    pub actions: Vec<DesktopAction>,
+    /// MIME types the app can open, from `MimeType=`
+    #[serde(default)]
+    pub mime_types: Vec<String>,
+}
+
+/// A `[Desktop Action <id>]` group, such as Firefox's "New Private Window"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DesktopAction {
+    pub id: String,
+    pub name: String,
+    pub exec: String,
+    pub icon: Option<String>,
 }
 
 impl DesktopApp {
-    fn from_desktop_file(path: &Path) -> Option<Self> {
+    pub(crate) fn from_desktop_file(path: &Path, desktop_id: String) -> Option<Self> {
         let content = fs::read_to_string(path).ok()?;
-        let mut app = DesktopApp {
-            name: String::new(),
-            exec: String::new(),
-            icon: None,
-            comment: None,
-            categories: Vec::new(),
-            no_display: false,
-            terminal: false,
+        let mut app = Self::from_desktop_entry(desktop_id, &content, &desktop_entry::current_locales())?;
+        app.desktop_file = Some(path.to_path_buf());
+        Some(app)
+    }
+
+    /// Parse the `[Desktop Entry]` group. Returns `None` for anything that isn't a
+    /// launchable application, but keeps hidden entries so they can mask others.
+    pub(crate) fn from_desktop_entry(desktop_id: String, content: &str, locales: &[String]) -> Option<Self> {
+        let file = DesktopFile::parse(content);
+        let entry = file.desktop_entry()?;
+
+        // Type is required by the spec, but tolerate files that leave it out
+        if entry.string("Type").is_some_and(|kind| kind != "Application") {
+            return None;
+        }
+
+        let app = DesktopApp {
+            desktop_id,
+            desktop_file: None,
+            name: entry.localized("Name", locales).unwrap_or_default(),
+            unlocalized_name: entry.string("Name").unwrap_or_default(),
+            generic_name: entry.localized("GenericName", locales),
+            keywords: entry.localized_list("Keywords", locales),
+            exec: entry.string("Exec").unwrap_or_default(),
+            try_exec: entry.string("TryExec").filter(|s| !s.is_empty()),
+            working_dir: entry.string("Path").filter(|s| !s.is_empty()),
+            icon: entry.string("Icon").filter(|s| !s.is_empty()),
+            comment: entry.localized("Comment", locales),
+            categories: entry.list("Categories"),
+            no_display: entry.boolean("NoDisplay"),
+            hidden: entry.boolean("Hidden"),
+            only_show_in: entry.list("OnlyShowIn"),
+            not_show_in: entry.list("NotShowIn"),
+            terminal: entry.boolean("Terminal"),
+            startup_wm_class: entry.string("StartupWMClass").filter(|s| !s.is_empty()),
+            actions: Self::parse_actions(&file, locales),
+            mime_types: entry.list("MimeType"),
         };
 
-        let mut in_desktop_entry = false;
-        for line in content.lines() {
-            let line = line.trim();
+        if !app.hidden && (app.name.is_empty() || app.exec.is_empty()) {
+            return None;
+        }
 
-            if line == "[Desktop Entry]" {
-                in_desktop_entry = true;
-                continue;
-            } else if line.starts_with('[') && line.ends_with(']') {
-                in_desktop_entry = false;
-                continue;
-            }
+        Some(app)
+    }
 
-            if !in_desktop_entry || line.is_empty() || line.starts_with('#') {
-                continue;
-            }
+    fn parse_actions(file: &DesktopFile, locales: &[String]) -> Vec<DesktopAction> {
+        let Some(entry) = file.desktop_entry() else {
+            return Vec::new();
+        };
 
-            if let Some((key, value)) = line.split_once('=') {
-                match key {
-                    "Name" => app.name = value.to_string(),
-                    "Exec" => app.exec = value.to_string(),
-                    "Icon" => app.icon = Some(value.to_string()),
-                    "Comment" => app.comment = Some(value.to_string()),
-                    "Categories" => {
-                        app.categories = value
-                            .split(';')
-                            .filter(|s| !s.is_empty())
-                            .map(|s| s.to_string())
-                            .collect();
-                    }
-                    "NoDisplay" => app.no_display = value.to_lowercase() == "true",
-                    "Terminal" => app.terminal = value.to_lowercase() == "true",
-                    _ => {}
-                }
-            }
+        entry
+            .list("Actions")
+            .into_iter()
+            .filter_map(|id| {
+                let group = file.group(&format!("Desktop Action {}", id))?;
+                let name = group.localized("Name", locales).filter(|s| !s.is_empty())?;
+                // Actions without Exec are D-Bus activated, which we can't launch
+                let exec = group.string("Exec").filter(|s| !s.is_empty())?;
+                Some(DesktopAction {
+                    id,
+                    name,
+                    exec,
+                    icon: group.string("Icon").filter(|s| !s.is_empty()),
+                })
+            })
+            .collect()
+    }
+
+    /// Whether the entry should be listed on one of `desktops` (from
+    /// `XDG_CURRENT_DESKTOP`) with the programs currently installed
+    pub fn is_visible(&self, desktops: &[String]) -> bool {
+        if self.hidden || self.no_display {
+            return false;
         }
 
-        if app.name.is_empty() || app.exec.is_empty() || app.no_display {
-            return None;
+        let on_current_desktop = |list: &[String]| list.iter().any(|d| desktops.contains(d));
+        if !self.only_show_in.is_empty() && !on_current_desktop(&self.only_show_in) {
+            return false;
+        }
+        if on_current_desktop(&self.not_show_in) {
+            return false;
         }
 
-        Some(app)
+        self.try_exec
+            .as_deref()
+            .is_none_or(|program| utils::find_executable(program).is_some())
     }
 
-    fn clean_exec_command(&self) -> String {
-        let mut cleaned = self.exec.clone();
-        let field_codes = [
-            "%f", "%F", "%u", "%U", "%d", "%D", "%n", "%N", "%i", "%c", "%k", "%v", "%m",
-        ];
-        for code in &field_codes {
-            cleaned = cleaned.replace(code, "");
+    /// Relevance for a query, also matching the generic name and keywords
+    fn relevance(&self, query: &str) -> f32 {
+        let primary = utils::relevance(query, &self.name, self.comment.as_deref().unwrap_or(""), &self.categories);
+
+        // "browser" should find Firefox through its GenericName or Keywords, just
+        // not as strongly as through its own name
+        let generic = self
+            .generic_name
+            .as_deref()
+            .map_or(0.0, |generic| utils::relevance(query, generic, "", &[]) * 0.8);
+        let query_lower = query.to_lowercase();
+        let keyword = if self.keywords.iter().any(|k| k.to_lowercase().starts_with(&query_lower)) {
+            utils::MatchKind::Description.relevance()
+        } else {
+            0.0
+        };
+
+        primary.max(generic).max(keyword)
+    }
+
+    /// Relevance of one of the app's actions. The query has to name the action, on
+    /// its own ("private window") or together with the app ("firefox private").
+    fn action_relevance(&self, action: &DesktopAction, query: &str) -> f32 {
+        let direct = match utils::match_kind(query, &action.name, "", &[]) {
+            Some(kind @ (utils::MatchKind::Exact | utils::MatchKind::TitlePrefix | utils::MatchKind::TitleContains)) => {
+                kind.relevance() * 0.9
+            }
+            _ => 0.0,
+        };
+
+        let combined = format!("{} {}", self.name, action.name).to_lowercase();
+        let action_name = action.name.to_lowercase();
+        let words: Vec<String> = query.to_lowercase().split_whitespace().map(str::to_string).collect();
+        let names_both = words.len() > 1
+            && words.iter().all(|word| combined.contains(word))
+            && words.iter().any(|word| action_name.contains(word));
+        let together = if names_both { utils::MatchKind::TitleContains.relevance() } else { 0.0 };
+
+        direct.max(together)
+    }
+
+    fn description(&self) -> String {
+        self.comment
+            .clone()
+            .or_else(|| self.generic_name.clone())
+            .unwrap_or_else(|| format!("Launch {}", self.name))
+    }
+
+    /// Launch data for one of the entry's Exec lines, passing `targets` (files or
+    /// URLs) through its field codes
+    fn launch_data(&self, exec: &str, icon: Option<&str>, targets: &[String]) -> ActionData {
+        let context = ExecContext {
+            name: &self.name,
+            icon,
+            desktop_file: self.desktop_file.as_deref(),
+        };
+        ActionData::Argv {
+            argv: desktop_entry::expand_exec(&desktop_entry::split_exec(exec), &context, targets),
+            working_dir: self.working_dir.clone(),
+        }
+    }
+
+    /// Names the app's windows may carry as their class: `StartupWMClass`, the
+    /// desktop file ID (and its last reverse-DNS component) and the program name
+    fn window_keys(&self) -> Vec<String> {
+        let stem = self.desktop_id.trim_end_matches(".desktop");
+        let mut keys: Vec<String> = self.startup_wm_class.iter().cloned().collect();
+        keys.push(stem.to_string());
+        if let Some((_, last)) = stem.rsplit_once('.') {
+            keys.push(last.to_string());
+        }
+        if let Some(program) = desktop_entry::split_exec(&self.exec).first() {
+            keys.push(Path::new(program).file_name().map_or(program.clone(), |name| name.to_string_lossy().to_string()));
         }
-        cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+        keys
+    }
+
+    /// Whether the app can be given files or URLs to open
+    fn accepts_targets(&self) -> bool {
+        desktop_entry::accepts_targets(&desktop_entry::split_exec(&self.exec))
+    }
+
+    /// A result that opens `target` with this app
+    fn open_with_result(&self, target: &str, provider_id: &str) -> ActionResult {
+        let target_name = Path::new(target)
+            .file_name()
+            .filter(|_| !target.contains("://"))
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| target.to_string());
+        let mut tags = vec!["open-with".to_string()];
+        tags.extend(self.categories.iter().cloned());
+
+        ActionResult::new_launch(
+            utils::generate_id("app_open", &format!("{}:{}", self.desktop_id, target)),
+            provider_id,
+            format!("Open {} with {}", target_name, self.name),
+            "",
+            self.terminal,
+        )
+        .with_description(target.to_string())
+        .with_data(self.launch_data(&self.exec, self.icon.as_deref(), &[target.to_string()]))
+        .with_metadata(ActionMetadata {
+            icon: self.icon.clone(),
+            category: self.categories.first().cloned(),
+            tags,
+            usage_count: 0,
+            last_used: None,
+            desktop_id: None,
+        })
+    }
+
+    /// Launch results for each of the app's actions
+    pub(crate) fn action_results(&self, provider_id: &str) -> Vec<ActionResult> {
+        self.actions
+            .iter()
+            .map(|action| {
+                let mut tags = vec!["action".to_string()];
+                tags.extend(self.categories.iter().cloned());
+
+                let icon = action.icon.clone().or_else(|| self.icon.clone());
+
+                ActionResult::new_launch(
+                    format!("{}:{}", app_id(&self.desktop_id), action.id),
+                    provider_id,
+                    format!("{}: {}", self.name, action.name),
+                    "",
+                    self.terminal,
+                )
+                .with_description(format!("{} action", self.name))
+                .with_data(self.launch_data(&action.exec, icon.as_deref(), &[]))
+                .with_metadata(ActionMetadata {
+                    icon,
+                    category: self.categories.first().cloned(),
+                    tags,
+                    usage_count: 0,
+                    last_used: None,
+                    desktop_id: None,
+                })
+            })
+            .collect()
     }
 }
 
+const DEFAULT_PRIORITY: u8 = 70;
+const DEFAULT_MAX_RESULTS: usize = 20;
+/// Relevance of every app listed without a query, before usage is added
+const LISTING_RELEVANCE: f32 = 0.02;
+/// How long the open windows checked for "focus existing" are reused while typing
+const WINDOW_LIST_TTL: Duration = Duration::from_secs(1);
+
 pub struct ApplicationProvider {
-    apps: Vec<DesktopApp>,
+    /// Shared with the blocking tasks that refresh it during a search
+    index: Arc<AppIndex>,
+    prefix: String,
+    enabled: bool,
+    priority: u8,
+    max_results: usize,
+    /// Set when results should focus an app's open window instead of launching it
+    windows: Option<Arc<dyn WindowBackend>>,
 }
 
 impl ApplicationProvider {
     pub fn new() -> Self {
-        Self { apps: Vec::new() }
+        Self {
+            index: Arc::new(AppIndex::new(&get_config().paths.cache_dir, Self::search_dirs(&[]))),
+            prefix: "app:".to_string(),
+            enabled: true,
+            priority: DEFAULT_PRIORITY,
+            max_results: DEFAULT_MAX_RESULTS,
+            windows: None,
+        }
     }
 
-    fn scan_desktop_files(&mut self) -> Result<(), ProviderError> {
-        let mut apps = Vec::new();
-        let mut seen_names = HashMap::new();
-
-        let search_paths = [
-            "/usr/share/applications",
-            "/usr/local/share/applications",
-            "~/.local/share/applications",
-        ];
+    /// The indexed apps. A refresh reads and parses files, so it runs on the blocking
+    /// pool rather than stalling the runtime the other providers search on.
+    async fn indexed_apps(&self) -> ProviderResult<Arc<Vec<DesktopApp>>> {
+        if let Some(apps) = self.index.cached_apps() {
+            return Ok(apps);
+        }
+        let index = Arc::clone(&self.index);
+        tokio::task::spawn_blocking(move || index.apps())
+            .await
+            .map_err(|e| ProviderError::Unavailable(format!("Application index refresh failed: {}", e)))
+    }
 
-        for path_str in &search_paths {
-            let expanded_path = shellexpand::tilde(path_str);
-            let path = Path::new(expanded_path.as_ref());
+    /// Point launch results of apps that already have a window at that window
+    async fn offer_focus_existing(&self, apps: &[DesktopApp], matches: &mut [ScoredResult]) {
+        let Some(backend) = &self.windows else {
+            return;
+        };
+        let open_windows = match backend.windows().await {
+            Ok(open_windows) => open_windows,
+            Err(e) => {
+                utils::log_debug(&format!("Couldn't list windows: {}", e));
+                return;
+            }
+        };
 
-            if !path.exists() {
+        for m in matches.iter_mut() {
+            // Only the app entries themselves; actions and "open with" always launch
+            let Some(desktop_id) = &m.result.metadata.desktop_id else {
                 continue;
+            };
+            if let Some(app) = apps.iter().find(|app| &app.desktop_id == desktop_id) {
+                windows::offer_focus_existing(&mut m.result, &app.window_keys(), &open_windows);
             }
+        }
+    }
 
-            let entries = fs::read_dir(path).map_err(|e| {
-                ProviderError::Command(format!(
-                    "Failed to read directory {}: {}",
-                    path.display(),
-                    e
-                ))
-            })?;
-
-            for entry in entries.flatten() {
-                let file_path = entry.path();
-
-                if file_path.extension().and_then(|s| s.to_str()) == Some("desktop") {
-                    if let Some(app) = DesktopApp::from_desktop_file(&file_path) {
-                        if let Some(existing_index) = seen_names.get(&app.name) {
-                            apps[*existing_index] = app;
-                        } else {
-                            seen_names.insert(app.name.clone(), apps.len());
-                            apps.push(app);
-                        }
-                    }
-                }
-            }
+    /// Apps that can open `target`, ranked by how well they match `app_query`
+    fn search_open_with(&self, apps: &[DesktopApp], target: &str, app_query: &str) -> Vec<ScoredResult> {
+        let mut matches: Vec<ScoredResult> = apps
+            .iter()
+            .filter(|app| app.accepts_targets())
+            .filter_map(|app| {
+                let relevance = app.relevance(app_query);
+                (relevance > 0.0).then(|| {
+                    ScoredResult::new(app.open_with_result(target, self.id()), relevance)
+                })
+            })
+            .collect();
+
+        matches.sort_by_key(|m| std::cmp::Reverse(m.score));
+        matches.truncate(self.max_results);
+        for m in &mut matches {
+            icons::resolve_result_icons(&mut m.result);
         }
+        utils::log_info(&format!("Open '{}' with '{}' - {} matching apps", target, app_query, matches.len()));
+        matches
+    }
 
-        utils::log_info(&format!("Scanned {} desktop applications", apps.len()));
-        self.apps = apps;
-        Ok(())
+    /// Expand the configured search paths, falling back to the XDG data directories
+    pub(crate) fn search_dirs(configured: &[String]) -> Vec<PathBuf> {
+        if configured.is_empty() {
+            return desktop_entry::application_dirs();
+        }
+
+        configured
+            .iter()
+            .map(|path| PathBuf::from(shellexpand::tilde(path).as_ref()))
+            .collect()
     }
 }
 
@@ -156,111 +411,101 @@ impl SearchProvider for ApplicationProvider {
         "Applications"
     }
 
-    fn can_handle(&self, query: &str) -> bool {
-        // Handle direct app: prefix or empty query (for top apps)
-        query.is_empty() ||
-        query.starts_with("app:") ||
-        query.starts_with("apps") ||
-        query == "apps" ||
-        (!query.starts_with("ai:") && !query.starts_with("ask:")) // Handle general queries unless they're AI queries
+    fn can_handle(&self, _query: &ParsedQuery) -> bool {
+        // Handles general queries, the empty query (top apps) and its own prefixes
+        self.enabled
+    }
+
+    fn prefixes(&self) -> Vec<String> {
+        vec![self.prefix.clone(), "apps".to_string()]
     }
 
     fn priority(&self) -> u8 {
-        70 // Higher priority for applications
+        self.priority // Higher priority than directories by default
     }
 
-    async fn search(&self, query: &str) -> ProviderResult<Vec<ScoredResult>> {
-        utils::log_info(&format!("ApplicationProvider::search called with query: '{}'", query));
+    async fn search(&self, query: &ParsedQuery) -> ProviderResult<Vec<ScoredResult>> {
+        if !self.enabled {
+            return Ok(Vec::new());
+        }
 
-        // Ensure apps are loaded
-        let mut provider = self.clone();
-        provider.scan_desktop_files()?;
+        utils::log_info(&format!("ApplicationProvider::search called with query: '{}'", query.raw));
 
-        let processed_query = if query.starts_with("app:") {
-            query.strip_prefix("app:").unwrap_or("").trim()
-        } else if query == "apps" {
-            ""
-        } else {
-            query
-        };
+        // Refreshes incrementally if the application directories changed
+        let apps = self.indexed_apps().await?;
+
+        // A bare prefix ("apps", "app:") lists every app; an empty query shows top apps
+        let list_all = query.is_scoped() && query.text.is_empty();
+        let processed_query = query.text.as_str();
+
+        if let Some((target, app_query)) = parse_open_with(processed_query) {
+            return Ok(self.search_open_with(&apps, &target, app_query));
+        }
 
         let mut matches = Vec::new();
 
-        for app in &provider.apps {
-            let app_id = utils::generate_id("app", &app.name);
+        for app in apps.iter() {
+            let app_id = app_id(&app.desktop_id);
             
-            let score = if processed_query.is_empty() {
-                if query == "apps" {
-                    // For "apps" keyword, show more apps with base scoring
-                    let usage_count = usage::get_usage_count(&app_id);
-                    if usage_count > 0 {
-                        let usage_boost = usage::get_usage_boost(&app_id);
-                        usage_boost
-                    } else {
-                        // Give all apps a base score when "apps" is used
-                        10
-                    }
-                } else {
-                    // Empty query - check for usage data
-                    let usage_count = usage::get_usage_count(&app_id);
-                    let usage_boost = usage::get_usage_boost(&app_id);
-                    
-                    utils::log_debug(&format!("App '{}': usage_count={}, usage_boost={}", 
-                        app.name, usage_count, usage_boost));
-                    
-                    if usage_count > 0 {
-                        usage_boost
-                    } 
-                    else {
-                        // If no apps have usage data, show some popular ones
-                        if matches.len() < 5 && is_common_app(&app.name) {
-                            20 // Give common apps a base score
-                        } else {
-                            continue;
-                        }
-                    }
-                }
-            } else {
-                // Calculate relevance score for non-empty queries
-                let base_score = utils::calculate_relevance_score(
-                    processed_query,
-                    &app.name,
-                    app.comment.as_deref().unwrap_or(""),
-                    &app.categories,
-                );
-                
-                if base_score > 0 {
-                    let usage_boost = usage::get_usage_boost(&app_id);
-                    base_score + usage_boost
-                } else {
+            // Without a query there is nothing to match. Usage is added by the provider
+            // manager, so every listed app starts from the same base relevance.
+            let relevance = if processed_query.is_empty() {
+                // Used apps, or some popular ones while nothing has been used yet
+                let listed = list_all
+                    || usage::get_usage_count(&app_id) > 0
+                    || (matches.len() < 5 && is_common_app(&app.name));
+                if !listed {
                     continue;
                 }
+                LISTING_RELEVANCE
+            } else {
+                // Usage is added by the provider manager, so this is the match alone
+                app.relevance(processed_query)
             };
 
+            let actions = app.action_results(self.id());
+
+            // "firefox private" finds Firefox's "New Private Window" action directly
+            if !processed_query.is_empty() {
+                for (action, action_result) in app.actions.iter().zip(&actions) {
+                    let action_relevance = app.action_relevance(action, processed_query);
+                    if action_relevance > 0.0 {
+                        matches.push(ScoredResult::new(action_result.clone(), action_relevance));
+                    }
+                }
+            }
+
+            if relevance <= 0.0 {
+                continue;
+            }
+
             let result = ActionResult {
                 id: app_id,
                 provider: self.id().to_string(),
                 action: ActionType::Launch { needs_terminal: app.terminal },
                 title: app.name.clone(),
-                description: app.comment.clone().unwrap_or_else(|| format!("Launch {}", app.name)),
-                data: ActionData::Command(app.clean_exec_command()),
+                description: app.description(),
+                data: app.launch_data(&app.exec, app.icon.as_deref(), &[]),
                 metadata: ActionMetadata {
                     icon: app.icon.clone(),
                     category: app.categories.first().cloned(),
                     tags: app.categories.clone(),
                     usage_count: 0,
                     last_used: None,
+                    desktop_id: Some(app.desktop_id.clone()),
                 },
+                secondary_actions: actions,
             };
 
-            matches.push(ScoredResult::new(result, score, self.id().to_string()));
+            matches.push(ScoredResult::new(result, relevance));
         }
 
-        // Sort by score and limit results
-        matches.sort_by(|a, b| b.score.cmp(&a.score));
+        // Sort by score and limit results; usage breaks ties so that frequently
+        // used apps survive the cut
+        matches.sort_by_cached_key(|m| std::cmp::Reverse((m.score, usage::get_usage_count(&m.result.id))));
         
         if processed_query.is_empty() {
-            if query == "apps" {
+            if list_all {
                 // For "apps" keyword, show more results (20-30 apps)
                 utils::log_info(&format!("'apps' keyword - returning {} apps", matches.len()));
             } else {
@@ -277,22 +522,119 @@ impl SearchProvider for ApplicationProvider {
 
         } else {
             // Normal limit for search queries
-            matches.truncate(20);
+            matches.truncate(self.max_results);
             utils::log_info(&format!("Search query '{}' - returning {} matches", processed_query, matches.len()));
         }
 
+        self.offer_focus_existing(&apps, &mut matches).await;
+
+        // Only the results that made the cut are worth an icon theme lookup
+        for m in &mut matches {
+            icons::resolve_result_icons(&mut m.result);
+        }
+
         Ok(matches)
     }
 
-    fn configure(&mut self, _config: &crate::config::Config) {
-        // Could use config to set search paths, etc.
+    fn configure(&mut self, config: &crate::config::Config) {
+        let settings = &config.providers.applications;
+        self.prefix = config.search.app_prefix.clone();
+        self.enabled = settings.enabled;
+        self.priority = settings.priority.unwrap_or(DEFAULT_PRIORITY);
+        self.max_results = settings.max_results;
+
+        let windows = &config.providers.windows;
+        self.windows = (windows.enabled && windows.focus_existing && HyprctlBackend::is_available())
+            .then(|| {
+                let backend = CachedBackend::new(Arc::new(HyprctlBackend), WINDOW_LIST_TTL);
+                Arc::new(backend) as Arc<dyn WindowBackend>
+            });
+
+        let search_dirs = Self::search_dirs(&settings.search_paths);
+        if search_dirs != self.index.search_dirs() {
+            self.index = Arc::new(AppIndex::new(&config.paths.cache_dir, search_dirs));
+        }
     }
-}
 
-impl Clone for ApplicationProvider {
-    fn clone(&self) -> Self {
-        Self {
-            apps: self.apps.clone(),
+    fn watch(&self) {
+        self.index.watch();
+    }
+
+    fn legacy_ids(&self) -> Option<Vec<(String, String)>> {
+        let apps = self.index.apps();
+        (!apps.is_empty()).then(|| legacy_app_ids(&apps))
+    }
+
+    async fn open_with(&self, target: &str) -> Vec<ActionResult> {
+        if !self.enabled {
+            return Vec::new();
+        }
+
+        let apps = match self.indexed_apps().await {
+            Ok(apps) => apps,
+            Err(e) => {
+                utils::log_warn(&format!("No apps to open '{}' with: {}", target, e));
+                return Vec::new();
+            }
+        };
+        let mime_type = mime::guess_mime_type(target);
+        let mime_apps = MimeApps::load();
+        let default = mime_apps.default_for(&mime_type, &apps).map(|app| app.desktop_id.clone());
+
+        let results: Vec<ActionResult> = mime_apps
+            .handlers(&mime_type, &apps)
+            .into_iter()
+            .filter(|app| app.accepts_targets())
+            .map(|app| {
+                let description = if default.as_ref() == Some(&app.desktop_id) {
+                    format!("Default for {}", mime_type)
+                } else {
+                    format!("Can open {}", mime_type)
+                };
+                let mut result = app.open_with_result(target, self.id()).with_description(description);
+                icons::resolve_result_icons(&mut result);
+                result
+            })
+            .collect();
+
+        utils::log_info(&format!("Open with for '{}' ({}) - {} apps", target, mime_type, results.len()));
+        results
+    }
+
+    async fn default_open(&self, target: &str) -> Option<ActionResult> {
+        if !self.enabled {
+            return None;
+        }
+
+        // Same handler lookup as "Open with", so the default shown there is what runs
+        let apps = self.indexed_apps().await.ok()?;
+        MimeApps::load()
+            .handlers(&mime::guess_mime_type(target), &apps)
+            .into_iter()
+            .find(|app| app.accepts_targets())
+            .map(|app| app.open_with_result(target, self.id()))
+    }
+
+    async fn health(&self) -> ProviderHealth {
+        if !self.enabled {
+            return ProviderHealth::warning("Disabled in config");
+        }
+
+        let apps = match self.indexed_apps().await {
+            Ok(apps) => apps,
+            Err(e) => return ProviderHealth::error(e.to_string()),
+        };
+        match apps.len() {
+            0 => ProviderHealth::error(format!(
+                "No desktop entries found in {}",
+                self.index
+                    .search_dirs()
+                    .iter()
+                    .map(|dir| dir.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            count => ProviderHealth::ok(format!("{} applications indexed", count)),
         }
     }
 }
@@ -320,6 +662,39 @@ fn is_common_app(app_name: &str) -> bool {
     common_apps.iter().any(|&common| app_lower.contains(common))
 }
 
+/// (old, new) usage IDs of `apps`. Old IDs hashed the unlocalized name, whatever
+/// locale the history was recorded in.
+fn legacy_app_ids(apps: &[DesktopApp]) -> Vec<(String, String)> {
+    apps.iter()
+        .map(|app| (utils::legacy_generate_id("app", &app.unlocalized_name), app_id(&app.desktop_id)))
+        .collect()
+}
+
+/// Result ID of an app, which stays the same when the app is renamed or localized
+pub fn app_id(desktop_id: &str) -> String {
+    format!("app:{}", desktop_id)
+}
+
+/// Split "open ~/notes.md with code" into the target to open and the app query.
+/// Paths are expanded and made absolute; URLs are kept as they are.
+fn parse_open_with(query: &str) -> Option<(String, &str)> {
+    let rest = query.get(..5).filter(|open| open.eq_ignore_ascii_case("open "))?;
+    let rest = &query[rest.len()..];
+    let split = rest.to_ascii_lowercase().rfind(" with ")?;
+    let target = rest[..split].trim().trim_matches(|c| c == '"' || c == '\'');
+    let app_query = rest[split + " with ".len()..].trim();
+    if target.is_empty() || app_query.is_empty() {
+        return None;
+    }
+
+    if target.contains("://") {
+        return Some((target.to_string(), app_query));
+    }
+    let path = PathBuf::from(shellexpand::tilde(target).as_ref());
+    let path = std::path::absolute(&path).unwrap_or(path);
+    Some((path.to_string_lossy().to_string(), app_query))
+}
+
 // Create fallback apps when no real apps are found
 fn create_fallback_apps() -> Vec<ScoredResult> {
     let fallback_apps = vec![
@@ -342,7 +717,50 @@ fn create_fallback_apps() -> Vec<ScoredResult> {
                 terminal,
             ).with_description(description.to_string());
             
-            ScoredResult::new(result, 100 - i as i32, "applications".to_string())
+            ScoredResult::new(result, 0.1 - i as f32 * 0.01)
         })
         .collect()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_with_fills_field_codes() {
+        let (target, app_query) = parse_open_with("Open ~/notes with spaces.md WITH code").unwrap();
+        let home = shellexpand::tilde("~").to_string();
+        assert_eq!(target, format!("{}/notes with spaces.md", home));
+        assert_eq!(app_query, "code");
+        assert_eq!(parse_open_with("open https://example.com with firefox").unwrap().0, "https://example.com");
+        assert!(parse_open_with("coffee with milk").is_none());
+        assert!(parse_open_with("open with code").is_none());
+
+        let entry = "[Desktop Entry]\nType=Application\nName=Code\nExec=code --new-window %F\nPath=/tmp\n";
+        let app = DesktopApp::from_desktop_entry("code.desktop".to_string(), entry, &[]).unwrap();
+        assert!(app.accepts_targets());
+
+        let result = app.open_with_result(&target, "applications");
+        assert_eq!(result.title, "Open notes with spaces.md with Code");
+        match result.data {
+            ActionData::Argv { argv, working_dir } => {
+                assert_eq!(argv, vec!["code".to_string(), "--new-window".to_string(), target]);
+                assert_eq!(working_dir.as_deref(), Some("/tmp"));
+            }
+            other => panic!("expected argv, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_legacy_ids_hash_the_unlocalized_name() {
+        let entry = "[Desktop Entry]\nType=Application\nName=Files\nName[de]=Dateien\nExec=nautilus\n";
+        let app = DesktopApp::from_desktop_entry("org.gnome.Nautilus.desktop".to_string(), entry, &["de".to_string()])
+            .unwrap();
+        assert_eq!(app.name, "Dateien");
+
+        assert_eq!(
+            legacy_app_ids(&[app]),
+            vec![(utils::legacy_generate_id("app", "Files"), "app:org.gnome.Nautilus.desktop".to_string())]
+        );
+    }
+}
@@ -0,0 +1,176 @@
+// src/providers/dedup.rs - Merging results from different providers that mean the same thing
+use crate::providers::ScoredResult;
+use crate::types::{ActionData, ActionResult, ActionType};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Keys identifying what a result acts on; two results sharing any key are duplicates.
+///
+/// Navigate and open results are keyed by their resolved path, launch results by desktop file
+/// ID and by command line (with the program reduced to its file name), so a fallback
+/// `firefox` entry matches the real `firefox.desktop`. Every result is also keyed by
+/// its ID.
+pub fn canonical_keys(result: &ActionResult) -> Vec<String> {
+    let mut keys = vec![format!("id:{}", result.id)];
+
+    match &result.action {
+        ActionType::Navigate { path, .. } | ActionType::Open { path } => keys.push(format!("path:{}", canonical_path(path))),
+        ActionType::Launch { .. } => {
+            if let Some(desktop_id) = &result.metadata.desktop_id {
+                keys.push(format!("desktop:{}", desktop_id));
+            }
+            let command = match &result.data {
+                ActionData::Command(command) => canonical_command(command.split_whitespace()),
+                ActionData::Argv { argv, .. } => canonical_command(argv.iter().map(String::as_str)),
+                _ => None,
+            };
+            if let Some(command) = command {
+                keys.push(format!("exec:{}", command));
+            }
+        }
+        ActionType::Focus { address } => keys.push(format!("window:{}", address)),
+        ActionType::AiResponse | ActionType::Custom { .. } => {}
+    }
+
+    keys
+}
+
+/// Collapse duplicates in place. The copy with the highest score is kept (the earlier
+/// one on ties) and takes on the tags and any missing metadata of the others. Launch
+/// results that only share a command line keep the desktop entry whatever the scores,
+/// since a bare command doesn't know whether it needs a terminal.
+pub fn merge_duplicates(results: &mut Vec<ScoredResult>) {
+    let mut merged: Vec<ScoredResult> = Vec::with_capacity(results.len());
+    let mut index_by_key: HashMap<String, usize> = HashMap::new();
+
+    for result in results.drain(..) {
+        let keys = canonical_keys(&result.result);
+        let existing = keys.iter().find_map(|key| index_by_key.get(key).map(|&index| (key, index)));
+
+        let index = match existing {
+            Some((key, index)) => {
+                let kept = &mut merged[index];
+                let replace = match (&kept.result.metadata.desktop_id, &result.result.metadata.desktop_id) {
+                    (None, Some(_)) if key.starts_with("exec:") => true,
+                    (Some(_), None) if key.starts_with("exec:") => false,
+                    _ => result.score > kept.score,
+                };
+                if replace {
+                    let loser = std::mem::replace(kept, result);
+                    absorb(kept, loser);
+                } else {
+                    absorb(kept, result);
+                }
+                index
+            }
+            None => {
+                merged.push(result);
+                merged.len() - 1
+            }
+        };
+
+        for key in keys {
+            index_by_key.entry(key).or_insert(index);
+        }
+    }
+
+    *results = merged;
+}
+
+fn absorb(kept: &mut ScoredResult, duplicate: ScoredResult) {
+    let kept_metadata = &mut kept.result.metadata;
+    let duplicate_metadata = duplicate.result.metadata;
+
+    for tag in duplicate_metadata.tags {
+        if !kept_metadata.tags.contains(&tag) {
+            kept_metadata.tags.push(tag);
+        }
+    }
+    kept_metadata.icon = kept_metadata.icon.take().or(duplicate_metadata.icon);
+    kept_metadata.category = kept_metadata.category.take().or(duplicate_metadata.category);
+    kept_metadata.desktop_id = kept_metadata.desktop_id.take().or(duplicate_metadata.desktop_id);
+    kept_metadata.usage_count = kept_metadata.usage_count.max(duplicate_metadata.usage_count);
+    kept_metadata.last_used = kept_metadata.last_used.max(duplicate_metadata.last_used);
+}
+
+fn canonical_path(path: &str) -> String {
+    let expanded = shellexpand::tilde(path).into_owned();
+    match std::fs::canonicalize(&expanded) {
+        Ok(resolved) => resolved.to_string_lossy().to_string(),
+        Err(_) => {
+            let trimmed = expanded.trim_end_matches('/');
+            if trimmed.is_empty() { "/".to_string() } else { trimmed.to_string() }
+        }
+    }
+}
+
+fn canonical_command<'a>(mut parts: impl Iterator<Item = &'a str>) -> Option<String> {
+    let program = parts.next()?;
+    let program = Path::new(program)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| program.to_string());
+
+    Some(std::iter::once(program).chain(parts.map(str::to_string)).collect::<Vec<_>>().join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scored(result: ActionResult, relevance: f32) -> ScoredResult {
+        ScoredResult::new(result, relevance)
+    }
+
+    #[test]
+    fn test_same_directory_from_two_sources_is_merged() {
+        let home = std::env::temp_dir();
+        let home = home.to_string_lossy();
+
+        let mut zoxide = ActionResult::new_navigate("dir_a", "directories", "zoxide", home.to_string());
+        zoxide.metadata.tags = vec!["directory".to_string(), "zoxide".to_string()];
+        let mut direct = ActionResult::new_navigate("dir_b", "directories", "direct", format!("{}/", home));
+        direct.metadata.tags = vec!["directory".to_string(), "direct".to_string()];
+
+        let mut results = vec![scored(zoxide, 0.5), scored(direct, 0.9)];
+        merge_duplicates(&mut results);
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].result.title, "direct");
+        assert_eq!(results[0].result.metadata.tags, vec!["directory", "direct", "zoxide"]);
+    }
+
+    #[test]
+    fn test_fallback_app_matches_desktop_entry() {
+        let mut desktop = ActionResult::new_launch("app_1", "applications", "Firefox", "/usr/bin/firefox", false);
+        desktop.metadata.desktop_id = Some("firefox.desktop".to_string());
+        desktop.metadata.icon = Some("firefox".to_string());
+        let mut fallback = ActionResult::new_launch("fallback_1", "applications", "Firefox", "firefox", false);
+        fallback.metadata.category = Some("Network".to_string());
+        let other = ActionResult::new_launch("app_2", "applications", "Private", "firefox --private-window", false);
+
+        let mut results = vec![scored(fallback, 0.1), scored(desktop, 0.1), scored(other, 0.1)];
+        merge_duplicates(&mut results);
+
+        assert_eq!(results.len(), 2);
+        // The desktop entry is kept in the fallback's place, filling in what it was missing
+        assert_eq!(results[0].result.id, "app_1");
+        assert_eq!(results[0].result.metadata.icon.as_deref(), Some("firefox"));
+        assert_eq!(results[0].result.metadata.category.as_deref(), Some("Network"));
+        assert_eq!(results[1].result.id, "app_2");
+    }
+
+    #[test]
+    fn test_desktop_entry_wins_over_path_executable() {
+        let mut desktop = ActionResult::new_launch("app:firefox.desktop", "applications", "Firefox", "firefox", false);
+        desktop.metadata.desktop_id = Some("firefox.desktop".to_string());
+        let executable = ActionResult::new_launch("run:firefox", "executables", "firefox", "/usr/bin/firefox", true);
+
+        let mut results = vec![scored(executable, 0.9), scored(desktop, 0.4)];
+        merge_duplicates(&mut results);
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].result.id, "app:firefox.desktop");
+        assert!(matches!(results[0].result.action, ActionType::Launch { needs_terminal: false }));
+    }
+}
@@ -0,0 +1,489 @@
+// src/providers/desktop_entry.rs - freedesktop Desktop Entry file parsing
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Export directories of flatpak and snap, which aren't always in `XDG_DATA_DIRS`
+/// (for example when the session wasn't started through a login shell)
+const EXTRA_DATA_DIRS: [&str; 3] = [
+    "~/.local/share/flatpak/exports/share",
+    "/var/lib/flatpak/exports/share",
+    "/var/lib/snapd/desktop",
+];
+
+pub const DESKTOP_ENTRY_GROUP: &str = "Desktop Entry";
+
+/// A parsed `.desktop` file: groups of keys, each key with its localized variants.
+///
+/// Values are stored raw and unescaped on access, since string and list values
+/// treat `\;` differently.
+#[derive(Debug, Default)]
+pub struct DesktopFile {
+    groups: HashMap<String, Group>,
+}
+
+#[derive(Debug, Default)]
+pub struct Group {
+    /// key -> locale ("" for the unlocalized value) -> raw value
+    entries: HashMap<String, HashMap<String, String>>,
+}
+
+impl DesktopFile {
+    pub fn parse(content: &str) -> Self {
+        let mut file = DesktopFile::default();
+        let mut current: Option<String> = None;
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                current = Some(name.to_string());
+                file.groups.entry(name.to_string()).or_default();
+                continue;
+            }
+
+            let (Some(group), Some((key, value))) = (&current, line.split_once('=')) else {
+                continue;
+            };
+            let key = key.trim_end();
+            let value = value.trim_start();
+
+            let (key, locale) = match key.split_once('[') {
+                Some((key, locale)) => (key, locale.trim_end_matches(']')),
+                None => (key, ""),
+            };
+
+            // The first occurrence wins, as duplicate keys are invalid anyway
+            if let Some(group) = file.groups.get_mut(group) {
+                group
+                    .entries
+                    .entry(key.to_string())
+                    .or_default()
+                    .entry(locale.to_string())
+                    .or_insert_with(|| value.to_string());
+            }
+        }
+
+        file
+    }
+
+    pub fn group(&self, name: &str) -> Option<&Group> {
+        self.groups.get(name)
+    }
+
+    pub fn desktop_entry(&self) -> Option<&Group> {
+        self.group(DESKTOP_ENTRY_GROUP)
+    }
+}
+
+impl Group {
+    /// Keys in the group, in no particular order
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    fn raw(&self, key: &str, locale: &str) -> Option<&str> {
+        self.entries.get(key)?.get(locale).map(String::as_str)
+    }
+
+    /// The unlocalized value with escape sequences resolved
+    pub fn string(&self, key: &str) -> Option<String> {
+        self.raw(key, "").map(unescape)
+    }
+
+    /// The best match for the given locales (most specific first), falling back to
+    /// the unlocalized value
+    pub fn localized(&self, key: &str, locales: &[String]) -> Option<String> {
+        locales
+            .iter()
+            .find_map(|locale| self.raw(key, locale))
+            .or_else(|| self.raw(key, ""))
+            .map(unescape)
+    }
+
+    pub fn boolean(&self, key: &str) -> bool {
+        self.raw(key, "").is_some_and(|value| value.trim() == "true")
+    }
+
+    pub fn list(&self, key: &str) -> Vec<String> {
+        self.raw(key, "").map(split_list).unwrap_or_default()
+    }
+
+    pub fn localized_list(&self, key: &str, locales: &[String]) -> Vec<String> {
+        locales
+            .iter()
+            .find_map(|locale| self.raw(key, locale))
+            .or_else(|| self.raw(key, ""))
+            .map(split_list)
+            .unwrap_or_default()
+    }
+}
+
+/// Resolve `\s`, `\n`, `\t`, `\r` and `\\`; unknown escapes are kept as-is
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => result.push(' '),
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some('\\') => result.push('\\'),
+            Some(other) => {
+                result.push('\\');
+                result.push(other);
+            }
+            None => result.push('\\'),
+        }
+    }
+
+    result
+}
+
+/// Split a `;`-separated list, honoring `\;` inside elements
+fn split_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(';') => current.push(';'),
+                Some(other) => {
+                    current.push('\\');
+                    current.push(other);
+                }
+                None => current.push('\\'),
+            },
+            ';' => items.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    items.push(current);
+
+    items
+        .iter()
+        .map(|item| unescape(item.trim()))
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Split an `Exec` value (already string-unescaped) into arguments, leaving field
+/// codes in place. Double quotes group an argument, and inside them `\"`, `` \` ``,
+/// `\$` and `\\` stand for the escaped character. Malformed values are handled
+/// leniently: an unterminated quote runs to the end of the line.
+pub fn split_exec(exec: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quoted = false;
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' => quoted = false,
+                '\\' => match chars.next() {
+                    Some(escaped @ ('"' | '`' | '$' | '\\')) => current.push(escaped),
+                    Some(other) => {
+                        current.push('\\');
+                        current.push(other);
+                    }
+                    None => current.push('\\'),
+                },
+                _ => current.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            ' ' | '\t' | '\n' => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            '"' => {
+                quoted = true;
+                in_arg = true;
+            }
+            '\\' => {
+                current.push(chars.next().unwrap_or('\\'));
+                in_arg = true;
+            }
+            _ => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+
+    args
+}
+
+/// What the `%i`, `%c` and `%k` field codes expand to
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExecContext<'a> {
+    pub name: &'a str,
+    pub icon: Option<&'a str>,
+    pub desktop_file: Option<&'a Path>,
+}
+
+/// Whether the command takes files or URLs through `%f`, `%F`, `%u` or `%U`
+pub fn accepts_targets(args: &[String]) -> bool {
+    args.iter()
+        .any(|arg| ["%f", "%F", "%u", "%U"].iter().any(|code| arg.contains(code)))
+}
+
+/// Expand field codes in `args` into the final argv. `targets` are the files or URLs
+/// to open; `%F`/`%U` take all of them, `%f`/`%u` only the first. Arguments that
+/// consisted only of field codes and expand to nothing are dropped.
+pub fn expand_exec(args: &[String], context: &ExecContext, targets: &[String]) -> Vec<String> {
+    let files: Vec<String> = targets.iter().map(|target| target_as_file(target)).collect();
+    let mut argv = Vec::with_capacity(args.len() + targets.len());
+
+    for arg in args {
+        match arg.as_str() {
+            "%F" => argv.extend(files.iter().cloned()),
+            "%U" => argv.extend(targets.iter().cloned()),
+            "%i" => {
+                if let Some(icon) = context.icon {
+                    argv.push("--icon".to_string());
+                    argv.push(icon.to_string());
+                }
+            }
+            _ => {
+                let expanded = expand_codes(arg, context, targets, &files);
+                if !expanded.is_empty() || arg.is_empty() {
+                    argv.push(expanded);
+                }
+            }
+        }
+    }
+
+    argv
+}
+
+fn expand_codes(arg: &str, context: &ExecContext, targets: &[String], files: &[String]) -> String {
+    let mut result = String::with_capacity(arg.len());
+    let mut chars = arg.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('%') => result.push('%'),
+            Some('f') => result.push_str(files.first().map(String::as_str).unwrap_or_default()),
+            Some('u') => result.push_str(targets.first().map(String::as_str).unwrap_or_default()),
+            // List codes embedded in a longer argument can't become separate arguments
+            Some('F') => result.push_str(&files.join(" ")),
+            Some('U') => result.push_str(&targets.join(" ")),
+            Some('i') => result.push_str(context.icon.unwrap_or_default()),
+            Some('c') => result.push_str(context.name),
+            Some('k') => {
+                if let Some(path) = context.desktop_file {
+                    result.push_str(&path.to_string_lossy());
+                }
+            }
+            // Deprecated (%d, %D, %n, %N, %v, %m) and unknown codes expand to nothing
+            _ => {}
+        }
+    }
+
+    result
+}
+
+/// `%f` takes local paths, so `file://` URLs are turned back into paths
+fn target_as_file(target: &str) -> String {
+    target.strip_prefix("file://").unwrap_or(target).to_string()
+}
+
+/// Locale keys to try for localized values, most specific first, from `LC_ALL`,
+/// `LC_MESSAGES` or `LANG`. For `sr_YU.UTF-8@Latn` that's `sr_YU@Latn`, `sr_YU`,
+/// `sr@Latn` and `sr`.
+pub fn current_locales() -> Vec<String> {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty());
+
+    locale.map(|locale| locale_variants(&locale)).unwrap_or_default()
+}
+
+pub fn locale_variants(locale: &str) -> Vec<String> {
+    if locale == "C" || locale == "POSIX" || locale.starts_with("C.") {
+        return Vec::new();
+    }
+
+    let (rest, modifier) = match locale.split_once('@') {
+        Some((rest, modifier)) => (rest, Some(modifier)),
+        None => (locale, None),
+    };
+    let rest = rest.split('.').next().unwrap_or(rest);
+    let (lang, country) = match rest.split_once('_') {
+        Some((lang, country)) => (lang, Some(country)),
+        None => (rest, None),
+    };
+
+    let mut variants = Vec::new();
+    if let (Some(country), Some(modifier)) = (country, modifier) {
+        variants.push(format!("{}_{}@{}", lang, country, modifier));
+    }
+    if let Some(country) = country {
+        variants.push(format!("{}_{}", lang, country));
+    }
+    if let Some(modifier) = modifier {
+        variants.push(format!("{}@{}", lang, modifier));
+    }
+    variants.push(lang.to_string());
+    variants
+}
+
+/// `applications` directories from `XDG_DATA_HOME` and `XDG_DATA_DIRS` plus the
+/// flatpak and snap exports, ordered from lowest to highest precedence so that later
+/// directories override earlier ones
+pub fn application_dirs() -> Vec<PathBuf> {
+    let env_dir = |var: &str| std::env::var(var).ok().filter(|value| !value.is_empty());
+    let data_home = env_dir("XDG_DATA_HOME").unwrap_or_else(|| "~/.local/share".to_string());
+    let data_dirs = env_dir("XDG_DATA_DIRS").unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    let by_precedence = std::iter::once(data_home.as_str())
+        .chain(data_dirs.split(':').filter(|dir| !dir.is_empty()))
+        .chain(EXTRA_DATA_DIRS);
+
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for dir in by_precedence {
+        let dir = PathBuf::from(shellexpand::tilde(dir).as_ref()).join("applications");
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs.reverse();
+    dirs
+}
+
+/// Desktop file ID for a file below an `applications` directory: the relative path
+/// with `/` replaced by `-`, so `applications/kde/konsole.desktop` is
+/// `kde-konsole.desktop`
+pub fn desktop_file_id(applications_dir: &Path, file: &Path) -> Option<String> {
+    let relative = file.strip_prefix(applications_dir).ok()?;
+    let parts: Vec<_> = relative.iter().map(|part| part.to_string_lossy()).collect();
+    (!parts.is_empty()).then(|| parts.join("-"))
+}
+
+/// Desktop environments named in `XDG_CURRENT_DESKTOP`, for `OnlyShowIn`/`NotShowIn`
+pub fn current_desktops() -> Vec<String> {
+    std::env::var("XDG_CURRENT_DESKTOP")
+        .map(|value| {
+            value
+                .split(':')
+                .filter(|desktop| !desktop.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIREFOX: &str = "\
+[Desktop Entry]
+Type=Application
+Name=Firefox
+Name[de]=Firefox Webbrowser
+Name[sr@Latn]=Fajerfoks
+GenericName=Web Browser
+Keywords=Internet;WWW;Browser;Web\\;Explorer;
+Comment=Line one\\nLine\\stwo
+Exec=firefox %u
+
+[Desktop Action new-window]
+Name=New Window
+Exec=firefox --new-window
+";
+
+    #[test]
+    fn test_localized_values_fall_back_to_less_specific_locales() {
+        let file = DesktopFile::parse(FIREFOX);
+        let entry = file.desktop_entry().unwrap();
+
+        assert_eq!(entry.localized("Name", &locale_variants("de_DE.UTF-8")).as_deref(), Some("Firefox Webbrowser"));
+        assert_eq!(entry.localized("Name", &locale_variants("sr_YU@Latn")).as_deref(), Some("Fajerfoks"));
+        assert_eq!(entry.localized("Name", &locale_variants("fr_FR")).as_deref(), Some("Firefox"));
+        assert_eq!(entry.localized("Name", &locale_variants("C")).as_deref(), Some("Firefox"));
+    }
+
+    #[test]
+    fn test_escapes_and_lists() {
+        let file = DesktopFile::parse(FIREFOX);
+        let entry = file.desktop_entry().unwrap();
+
+        assert_eq!(entry.string("Comment").as_deref(), Some("Line one\nLine two"));
+        assert_eq!(entry.list("Keywords"), vec!["Internet", "WWW", "Browser", "Web;Explorer"]);
+
+        // Keys in other groups don't leak into the main entry
+        assert_eq!(entry.string("Exec").as_deref(), Some("firefox %u"));
+        let action = file.group("Desktop Action new-window").unwrap();
+        assert_eq!(action.string("Exec").as_deref(), Some("firefox --new-window"));
+    }
+
+    #[test]
+    fn test_exec_quoting_and_field_codes() {
+        let args = split_exec(r#"sh -c "echo \"\$HOME\" 'a  b'" %U  --name=%c %i %k"#);
+        assert_eq!(args, vec!["sh", "-c", r#"echo "$HOME" 'a  b'"#, "%U", "--name=%c", "%i", "%k"]);
+        assert!(accepts_targets(&args));
+
+        let context = ExecContext {
+            name: "Text Editor",
+            icon: Some("accessories-text-editor"),
+            desktop_file: Some(Path::new("/usr/share/applications/editor.desktop")),
+        };
+        let targets = vec!["/tmp/a b.md".to_string(), "https://example.com".to_string()];
+        assert_eq!(
+            expand_exec(&args, &context, &targets),
+            vec![
+                "sh",
+                "-c",
+                r#"echo "$HOME" 'a  b'"#,
+                "/tmp/a b.md",
+                "https://example.com",
+                "--name=Text Editor",
+                "--icon",
+                "accessories-text-editor",
+                "/usr/share/applications/editor.desktop",
+            ]
+        );
+
+        // Without targets or an icon, codes that stand alone disappear entirely
+        let args = split_exec("code %f %i --literal=100%%");
+        assert_eq!(expand_exec(&args, &ExecContext::default(), &[]), vec!["code", "--literal=100%"]);
+        assert!(!accepts_targets(&split_exec("htop")));
+    }
+
+    #[test]
+    fn test_desktop_file_ids_join_subdirectories() {
+        let root = Path::new("/usr/share/applications");
+        assert_eq!(desktop_file_id(root, &root.join("firefox.desktop")).as_deref(), Some("firefox.desktop"));
+        assert_eq!(
+            desktop_file_id(root, &root.join("kde/org.kde.konsole.desktop")).as_deref(),
+            Some("kde-org.kde.konsole.desktop")
+        );
+        assert_eq!(desktop_file_id(root, Path::new("/opt/app.desktop")), None);
+    }
+}
@@ -1,18 +1,60 @@
 // src/providers/directories.rs
 use crate::{
-    providers::{ScoredResult, SearchProvider},
+    config::{DirectoryBackend, NavigateHandlerConfig},
+    providers::{ParsedQuery, ProviderHealth, ScoredResult, SearchProvider},
+    services::{frecency, icons, navigate},
     types::{ActionData, ActionMetadata, ActionResult, ActionType, ProviderError, ProviderResult},
     utils,
 };
 use async_trait::async_trait;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 use tokio::process::Command;
 
-pub struct DirectoryProvider;
+const DEFAULT_PRIORITY: u8 = 40;
+/// Frecency score that earns half of the extra relevance; a directory visited a
+/// few times a day scores around this in zoxide
+const HALF_RELEVANCE_SCORE: f64 = 20.0;
+
+pub struct DirectoryProvider {
+    enabled: bool,
+    priority: u8,
+    max_results: usize,
+    zoxide_path: String,
+    backend: DirectoryBackend,
+    /// Directories never offered
+    exclude: Vec<String>,
+    /// Only directories below this one are offered
+    base_dir: Option<String>,
+    /// Ways of opening a directory, by name
+    handlers: BTreeMap<String, NavigateHandlerConfig>,
+    /// Handler a chosen directory is opened with; the others are offered as
+    /// secondary actions
+    default_handler: String,
+}
 
 impl DirectoryProvider {
     pub fn new() -> Self {
-        Self
+        Self {
+            enabled: true,
+            priority: DEFAULT_PRIORITY,
+            max_results: 15,
+            zoxide_path: "zoxide".to_string(),
+            backend: DirectoryBackend::Auto,
+            exclude: Vec::new(),
+            base_dir: None,
+            handlers: navigate::handlers(&crate::config::Config::default()),
+            default_handler: navigate::FALLBACK_HANDLER.to_string(),
+        }
+    }
+
+    /// Whether matches come from zoxide rather than the built-in database
+    fn uses_zoxide(&self) -> bool {
+        match self.backend {
+            DirectoryBackend::Zoxide => true,
+            DirectoryBackend::Native => false,
+            DirectoryBackend::Auto => utils::find_executable(&self.zoxide_path).is_some(),
+        }
     }
 }
 
@@ -26,26 +68,31 @@ impl SearchProvider for DirectoryProvider {
         "Directories"
     }
 
-    fn can_handle(&self, query: &str) -> bool {
+    fn can_handle(&self, query: &ParsedQuery) -> bool {
         // Don't handle empty queries (leave those to applications)
-        // Don't handle AI queries
-        !query.is_empty()
-            && !query.starts_with("ai:")
-            && !query.starts_with("ask:")
-            && !query.starts_with("app:")
+        self.enabled && !query.text.is_empty()
     }
 
     fn priority(&self) -> u8 {
-        40 // Lower priority than applications
+        self.priority // Lower priority than applications by default
     }
 
-    async fn search(&self, query: &str) -> ProviderResult<Vec<ScoredResult>> {
+    async fn search(&self, query: &ParsedQuery) -> ProviderResult<Vec<ScoredResult>> {
+        if !self.enabled {
+            return Ok(Vec::new());
+        }
+
+        let query = query.text.as_str();
         let mut results = Vec::new();
 
-        // Try zoxide first
-        match self.search_with_zoxide(query).await {
-            Ok(mut zoxide_results) => results.append(&mut zoxide_results),
-            Err(e) => utils::log_warn(&format!("Zoxide search failed: {}", e)),
+        // Frecent directories first, from zoxide or the built-in database
+        if self.uses_zoxide() {
+            match self.search_with_zoxide(query).await {
+                Ok(mut zoxide_results) => results.append(&mut zoxide_results),
+                Err(e) => utils::log_warn(&format!("Zoxide search failed: {}", e)),
+            }
+        } else {
+            results.append(&mut self.search_frecency(query));
         }
 
         // Try direct path matching
@@ -64,23 +111,86 @@ impl SearchProvider for DirectoryProvider {
         });
 
         // Sort by score and limit
-        results.sort_by(|a, b| b.score.cmp(&a.score));
-        results.truncate(15);
+        results.sort_by_key(|r| std::cmp::Reverse(r.score));
+        results.truncate(self.max_results);
+        for result in &mut results {
+            icons::resolve_result_icons(&mut result.result);
+        }
 
         Ok(results)
     }
 
-    fn configure(&mut self, _config: &crate::config::Config) {
-        // Could configure zoxide options, etc.
+    fn configure(&mut self, config: &crate::config::Config) {
+        let settings = &config.providers.directories;
+        self.enabled = settings.enabled;
+        self.priority = settings.priority.unwrap_or(DEFAULT_PRIORITY);
+        self.max_results = settings.max_results;
+        self.zoxide_path = shellexpand::tilde(&settings.zoxide_path).into_owned();
+        self.backend = settings.backend;
+        let expand = |path: &String| shellexpand::tilde(path).trim_end_matches('/').to_string();
+        self.exclude = settings.exclude.iter().map(expand).collect();
+        self.base_dir = settings.base_dir.as_ref().map(expand);
+        self.handlers = navigate::handlers(config);
+        self.default_handler = config.navigate.default_handler.clone();
+    }
+
+    fn legacy_ids(&self) -> Option<Vec<(String, String)>> {
+        let output = std::process::Command::new(&self.zoxide_path).args(["query", "-l"]).output();
+        let output = output.ok().filter(|output| output.status.success())?;
+
+        let renames = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(|path| (utils::legacy_generate_id("dir", path), utils::generate_id("dir", path)))
+            .collect();
+        Some(renames)
+    }
+
+    async fn open_with(&self, target: &str) -> Vec<ActionResult> {
+        if !Path::new(target).is_dir() {
+            return Vec::new();
+        }
+
+        let mut results: Vec<ActionResult> = self
+            .handlers
+            .iter()
+            .filter(|(name, _)| **name != self.default_handler)
+            .map(|(name, handler)| self.handler_result(target, name, handler))
+            .collect();
+        for result in &mut results {
+            icons::resolve_result_icons(result);
+        }
+        results
+    }
+
+    async fn health(&self) -> ProviderHealth {
+        if !self.enabled {
+            return ProviderHealth::warning("Disabled in config");
+        }
+
+        match (self.backend, utils::find_executable(&self.zoxide_path)) {
+            (DirectoryBackend::Native, _) | (DirectoryBackend::Auto, None) => match frecency::len() {
+                Some(count) => ProviderHealth::ok(format!("Built-in database, {} directories", count)),
+                None => ProviderHealth::error("Built-in directory database not loaded"),
+            },
+            (_, Some(path)) => ProviderHealth::ok(format!("Using {}", path.display())),
+            (DirectoryBackend::Zoxide, None) => ProviderHealth::error(format!(
+                "'{}' not found; only literal paths will match",
+                self.zoxide_path
+            )),
+        }
     }
 }
 
 impl DirectoryProvider {
     async fn search_with_zoxide(&self, query: &str) -> ProviderResult<Vec<ScoredResult>> {
-        let output = Command::new("zoxide")
-            .arg("query")
-            .arg("-s")
-            .arg(query)
+        // `exclude` and `base_dir` are applied here rather than passed on, since not
+        // every zoxide release takes them as flags
+        let output = Command::new(&self.zoxide_path)
+            .args(["query", "--list", "--score", "--"])
+            .args(query.split_whitespace())
+            .kill_on_drop(true) // Don't leave zoxide running if the search times out
             .output()
             .await
             .map_err(|e| ProviderError::Command(format!("Failed to execute zoxide: {}", e)))?;
@@ -93,39 +203,97 @@ impl DirectoryProvider {
             return Err(ProviderError::Command(format!("Zoxide failed: {}", stderr)));
         }
 
-        let result_str = String::from_utf8_lossy(&output.stdout);
-        let mut results = Vec::new();
+        // zoxide only prints scores; its database also has the visit times
+        let last_accessed: HashMap<String, i64> = frecency::read_zoxide_db()
+            .map(|entries| entries.iter().map(|entry| (entry.path.clone(), entry.last_accessed)).collect())
+            .unwrap_or_default();
+        let now = chrono::Utc::now().timestamp();
 
-        for line in result_str.lines() {
-            let path = line.split_whitespace().last().unwrap_or("").trim();
-            if !path.is_empty() && Path::new(path).is_dir() {
-                let action_id = utils::generate_id("dir", path);
-                let result = ActionResult {
-                    id: action_id,
-                    provider: self.id().to_string(),
-                    action: ActionType::Navigate {
-                        path: path.to_string(),
-                    },
-                    title: path.to_string(),
-                    description: format!("Navigate to {}", path),
-                    data: ActionData::Path(path.to_string()),
-                    metadata: ActionMetadata {
-                        icon: Some("folder".to_string()),
-                        category: Some("directory".to_string()),
-                        tags: vec!["directory".to_string(), "zoxide".to_string()],
-                        usage_count: 0,
-                        last_used: None,
-                    },
-                };
-
-                // Score based on zoxide ranking (higher is better)
-                let score = 100; // Base score for zoxide matches
-
-                results.push(ScoredResult::new(result, score, self.id().to_string()));
-            }
+        Ok(frecency::parse_scored_paths(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .filter(|(path, _)| self.in_scope(path) && Path::new(path).is_dir())
+            .take(self.max_results)
+            .map(|(path, score)| self.frecent_result(&path, score, last_accessed.get(&path).copied(), now, "zoxide"))
+            .collect())
+    }
+
+    /// Matches from the built-in frecency database, split into keywords like zoxide
+    fn search_frecency(&self, query: &str) -> Vec<ScoredResult> {
+        let keywords: Vec<&str> = query.split_whitespace().collect();
+        let now = chrono::Utc::now().timestamp();
+        frecency::query(&keywords)
+            .into_iter()
+            .filter(|(entry, _)| self.in_scope(&entry.path))
+            .take(self.max_results)
+            .map(|(entry, score)| self.frecent_result(&entry.path, score, Some(entry.last_accessed), now, "frecency"))
+            .collect()
+    }
+
+    /// Whether `path` passes the `exclude` and `base_dir` settings
+    fn in_scope(&self, path: &str) -> bool {
+        !self.exclude.iter().any(|excluded| excluded == path)
+            && self.base_dir.as_ref().is_none_or(|base_dir| Path::new(path).starts_with(base_dir))
+    }
+
+    /// A directory from zoxide or the built-in database, ranked by its frecency score
+    fn frecent_result(&self, path: &str, score: f64, last_accessed: Option<i64>, now: i64, source: &str) -> ScoredResult {
+        let mut description = format!("Navigate to {} · score {:.1}", path, score);
+        if let Some(last_accessed) = last_accessed {
+            description.push_str(&format!(", visited {}", format_elapsed(now - last_accessed)));
         }
 
-        Ok(results)
+        let result = self.directory_result(path, source).with_description(description);
+        ScoredResult::new(result, score_relevance(score))
+    }
+
+    fn directory_result(&self, path: &str, source: &str) -> ActionResult {
+        ActionResult {
+            id: utils::generate_id("dir", path),
+            provider: self.id().to_string(),
+            action: ActionType::Navigate {
+                path: path.to_string(),
+                handler: None,
+            },
+            title: path.to_string(),
+            description: format!("Navigate to {}", path),
+            data: ActionData::Path(path.to_string()),
+            metadata: ActionMetadata {
+                icon: Some("folder".to_string()),
+                category: Some("directory".to_string()),
+                tags: vec!["directory".to_string(), source.to_string()],
+                usage_count: 0,
+                last_used: None,
+                desktop_id: None,
+            },
+            secondary_actions: Vec::new(),
+        }
+    }
+
+    /// A secondary action that opens `path` with a handler other than the default
+    fn handler_result(&self, path: &str, name: &str, handler: &NavigateHandlerConfig) -> ActionResult {
+        let title = match handler.label.as_str() {
+            "" => format!("Open with {}", name),
+            label => label.to_string(),
+        };
+
+        ActionResult {
+            id: utils::generate_id("dir_handler", &format!("{}:{}", name, path)),
+            provider: self.id().to_string(),
+            action: ActionType::Navigate {
+                path: path.to_string(),
+                handler: Some(name.to_string()),
+            },
+            title,
+            description: path.to_string(),
+            data: ActionData::Path(path.to_string()),
+            metadata: ActionMetadata {
+                icon: handler.icon.clone().or_else(|| Some("folder".to_string())),
+                category: Some("directory".to_string()),
+                tags: vec!["directory".to_string(), name.to_string()],
+                ..Default::default()
+            },
+            secondary_actions: Vec::new(),
+        }
     }
 
     async fn search_direct_path(&self, query: &str) -> ProviderResult<Vec<ScoredResult>> {
@@ -133,33 +301,12 @@ impl DirectoryProvider {
         let path = Path::new(&expanded_query);
 
         if path.is_dir() {
-            let action_id = utils::generate_id("dir", &expanded_query);
-            let result = ActionResult {
-                id: action_id,
-                provider: self.id().to_string(),
-                action: ActionType::Navigate {
-                    path: expanded_query.clone(),
-                },
-                title: expanded_query.clone(),
-                description: format!("Navigate to {}", expanded_query),
-                data: ActionData::Path(expanded_query),
-                metadata: ActionMetadata {
-                    icon: Some("folder".to_string()),
-                    category: Some("directory".to_string()),
-                    tags: vec!["directory".to_string(), "direct".to_string()],
-                    usage_count: 0,
-                    last_used: None,
-                },
-            };
-
-            // Direct path matches get higher priority
-            let score = 150;
-
-            Ok(vec![ScoredResult::new(
-                result,
-                score,
-                self.id().to_string(),
-            )])
+            let result = self.directory_result(&expanded_query, "direct");
+
+            // The user typed this exact path
+            let relevance = 1.0;
+
+            Ok(vec![ScoredResult::new(result, relevance)])
         } else {
             Ok(Vec::new())
         }
@@ -171,3 +318,78 @@ impl Default for DirectoryProvider {
         Self::new()
     }
 }
+
+/// Map a frecency score onto 0.3..0.9, so often and recently visited directories
+/// outrank stale ones while an exact typed path (1.0) still comes first
+fn score_relevance(score: f64) -> f32 {
+    (0.3 + 0.6 * score / (score + HALF_RELEVANCE_SCORE)) as f32
+}
+
+/// "5 minutes ago", "2 days ago" and so on
+fn format_elapsed(seconds: i64) -> String {
+    let (count, unit) = match seconds.max(0) {
+        s if s < 60 => return "just now".to_string(),
+        s if s < 60 * 60 => (s / 60, "minute"),
+        s if s < 24 * 60 * 60 => (s / (60 * 60), "hour"),
+        s => (s / (24 * 60 * 60), "day"),
+    };
+    format!("{} {}{} ago", count, unit, if count == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frequent_directories_outrank_stale_ones() {
+        let provider = DirectoryProvider::new();
+        let now = 1_700_000_000;
+        let project = provider.frecent_result("/home/me/dev/wayfindr", 96.0, Some(now - 300), now, "zoxide");
+        let stale = provider.frecent_result("/home/me/old/wayfindr", 0.5, Some(now - 90 * 86400), now, "zoxide");
+
+        assert!(project.relevance > stale.relevance);
+        assert!(project.relevance < 1.0 && stale.relevance >= 0.3);
+        assert_eq!(project.result.description, "Navigate to /home/me/dev/wayfindr · score 96.0, visited 5 minutes ago");
+        assert_eq!(stale.result.description, "Navigate to /home/me/old/wayfindr · score 0.5, visited 90 days ago");
+        assert_eq!(format_elapsed(3600), "1 hour ago");
+    }
+
+    #[test]
+    fn test_exclude_and_base_dir_scope_matches() {
+        let mut provider = DirectoryProvider::new();
+        provider.exclude = vec!["/home/me/dev/scratch".to_string()];
+        provider.base_dir = Some("/home/me/dev".to_string());
+
+        assert!(provider.in_scope("/home/me/dev/wayfindr"));
+        assert!(!provider.in_scope("/home/me/dev/scratch"));
+        assert!(provider.in_scope("/home/me/dev/scratch/keep"));
+        assert!(!provider.in_scope("/home/me/devtools"));
+    }
+
+    #[tokio::test]
+    async fn test_other_navigate_handlers_are_offered_for_directories() {
+        let mut provider = DirectoryProvider::new();
+        provider.default_handler = "file_manager".to_string();
+        let dir = std::env::temp_dir();
+        let dir = dir.to_string_lossy();
+
+        let handlers: Vec<_> = provider
+            .open_with(&dir)
+            .await
+            .into_iter()
+            .map(|result| match result.action {
+                ActionType::Navigate { path, handler } => (path, handler.unwrap(), result.title),
+                action => panic!("unexpected action {:?}", action),
+            })
+            .collect();
+        assert_eq!(
+            handlers,
+            vec![
+                (dir.to_string(), "editor".to_string(), "Open in editor".to_string()),
+                (dir.to_string(), "shell".to_string(), "cd in the calling shell".to_string()),
+                (dir.to_string(), "terminal".to_string(), "Open in terminal".to_string()),
+            ]
+        );
+        assert!(provider.open_with("/nonexistent/wayfindr").await.is_empty());
+    }
+}
@@ -1,6 +1,6 @@
 // src/providers/dynamic.rs
 use crate::{
-    providers::{ScoredResult, SearchProvider},
+    providers::{ParsedQuery, ProviderHealth, ScoredResult, SearchProvider},
     types::{ActionData, ActionMetadata, ActionResult, ActionType, ProviderError, ProviderResult},
     utils,
 };
@@ -13,7 +13,8 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{collections::HashMap, sync::atomic::{AtomicBool, Ordering}};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DynamicProviderConfig {
@@ -67,8 +68,12 @@ pub struct MatcherConfig {
 }
 
 pub struct DynamicProvider {
+    // Leaked once so `SearchProvider::id` can hand out a `&'static str`
+    id: &'static str,
     config: DynamicProviderConfig,
     regex_matchers: Vec<(Regex, MatcherConfig)>,
+    /// Matcher patterns that failed to compile, with the compile error
+    invalid_patterns: Vec<(String, String)>,
     client: Client,
     handlebars: Handlebars<'static>,
     auth_failed: AtomicBool,
@@ -77,6 +82,7 @@ pub struct DynamicProvider {
 impl DynamicProvider {
     pub fn from_config(config: DynamicProviderConfig) -> Result<Self, ProviderError> {
         let mut regex_matchers = Vec::new();
+        let mut invalid_patterns = Vec::new();
         
         // Compile regex patterns
         for matcher in &config.matchers {
@@ -87,6 +93,7 @@ impl DynamicProvider {
                         "Invalid regex pattern '{}' in provider '{}': {}",
                         matcher.pattern, config.provider.id, e
                     ));
+                    invalid_patterns.push((matcher.pattern.clone(), e.to_string()));
                 }
             }
         }
@@ -95,8 +102,10 @@ impl DynamicProvider {
         handlebars.set_strict_mode(false);
         
         Ok(Self {
+            id: Box::leak(config.provider.id.clone().into_boxed_str()),
             config,
             regex_matchers,
+            invalid_patterns,
             client: Client::new(),
             handlebars,
             auth_failed: AtomicBool::new(false),
@@ -108,11 +117,12 @@ impl DynamicProvider {
             .and_then(|env_var| std::env::var(env_var).ok())
     }
 
+    /// Providers without `api_key_env` don't need a key
     fn check_api_key_availability(&self) -> bool {
-        self.get_api_key().is_some()
+        self.config.api.api_key_env.is_none() || self.get_api_key().is_some()
     }
 
-    fn create_api_key_help_result(&self, query: &str) -> ActionResult {
+    fn create_api_key_help_result(&self) -> ActionResult {
         let env_var = self.config.api.api_key_env.as_deref().unwrap_or("API_KEY");
         let action_id = utils::generate_id(&self.config.provider.id, "setup");
         
@@ -136,11 +146,13 @@ impl DynamicProvider {
                 tags: vec!["setup".to_string(), "api-key".to_string()],
                 usage_count: 0,
                 last_used: None,
+                desktop_id: None,
             },
+            secondary_actions: Vec::new(),
         }
     }
 
-    fn create_auth_failed_result(&self, query: &str) -> ActionResult {
+    fn create_auth_failed_result(&self) -> ActionResult {
         let env_var = self.config.api.api_key_env.as_deref().unwrap_or("API_KEY");
         let action_id = utils::generate_id(&self.config.provider.id, "auth_failed");
         
@@ -164,7 +176,9 @@ impl DynamicProvider {
                 tags: vec!["authentication".to_string(), "api-key".to_string()],
                 usage_count: 0,
                 last_used: None,
+                desktop_id: None,
             },
+            secondary_actions: Vec::new(),
         }
     }
     
@@ -175,11 +189,13 @@ impl DynamicProvider {
             .unwrap_or_else(|_| "Orlando,FL,US".to_string())
     }
     
+    /// Run a configured command against the provider's API. Dropping the returned future,
+    /// as happens when a newer query supersedes this one, aborts the in-flight request.
     async fn execute_command(
         &self,
         command: &CommandConfig,
         query: &str,
-        use_location: bool,
+        _use_location: bool,
     ) -> Result<String, ProviderError> {
         let mut context = HashMap::new();
         
@@ -272,7 +288,7 @@ impl DynamicProvider {
             
             // Handle query|location pattern
             if *key == "query" && value.is_empty() {
-                result = result.replace("{{query|location}}", &context.get("location").unwrap_or(&String::new()));
+                result = result.replace("{{query|location}}", context.get("location").unwrap_or(&String::new()));
             }
         }
         
@@ -283,32 +299,27 @@ impl DynamicProvider {
 #[async_trait]
 impl SearchProvider for DynamicProvider {
     fn id(&self) -> &'static str {
-        Box::leak(self.config.provider.id.clone().into_boxed_str())
+        self.id
     }
     
     fn name(&self) -> &str {
         &self.config.provider.name
     }
     
-    fn can_handle(&self, query: &str) -> bool {
+    fn can_handle(&self, query: &ParsedQuery) -> bool {
         if !self.config.provider.enabled {
             return false;
         }
 
-        // Don't handle queries that are clearly for other providers
-        if query.is_empty() || 
-           query == "apps" || 
-           query.starts_with("app:") ||
-           query.starts_with("ai:") || 
-           query.starts_with("ask:") {
-            return false;
+        // Routed here by one of our prefixes
+        if query.is_scoped_to(self.id) {
+            return true;
         }
-        
-        // Check prefixes
-        for prefix in &self.config.triggers.prefixes {
-            if query.starts_with(prefix) {
-                return true;
-            }
+
+        // Queries routed to other providers never reach us, so only the text is left
+        let query = query.text.as_str();
+        if query.is_empty() {
+            return false;
         }
         
         // Check patterns
@@ -329,19 +340,26 @@ impl SearchProvider for DynamicProvider {
         false
     }
     
+    fn prefixes(&self) -> Vec<String> {
+        self.config.triggers.prefixes.clone()
+    }
+
     fn priority(&self) -> u8 {
         self.config.provider.priority
     }
+
+    fn default_timeout(&self) -> Option<Duration> {
+        Some(Duration::from_secs(5)) // Remote APIs need more headroom than local providers
+    }
     
-    async fn search(&self, query: &str) -> ProviderResult<Vec<ScoredResult>> {
+    async fn search(&self, query: &ParsedQuery) -> ProviderResult<Vec<ScoredResult>> {
         if !self.check_api_key_availability() {
-            let help_result = self.create_api_key_help_result(query);
+            let help_result = self.create_api_key_help_result();
             return Ok(
                 vec![
                     ScoredResult::new(
                         help_result,
-                        10, // Low score since it's just a setup message
-                        self.config.provider.id.clone()
+                        0.1, // Low relevance since it's just a setup message
                     )
                 ]
             )
@@ -349,18 +367,12 @@ impl SearchProvider for DynamicProvider {
 
         // Check if authentication has previously failed
         if self.auth_failed.load(Ordering::Relaxed) {
-            let auth_failed_result = self.create_auth_failed_result(query);
-            return Ok(vec![ScoredResult::new(auth_failed_result, 10, self.config.provider.id.clone())]);
+            let auth_failed_result = self.create_auth_failed_result();
+            return Ok(vec![ScoredResult::new(auth_failed_result, 0.1)]);
         }
         
-        // Strip known prefixes
-        let mut processed_query = query;
-        for prefix in &self.config.triggers.prefixes {
-            if let Some(stripped) = query.strip_prefix(prefix) {
-                processed_query = stripped.trim();
-                break;
-            }
-        }
+        // The router has already stripped our prefix
+        let processed_query = query.text.as_str();
         
         // Find matching command
         let mut command_id = None;
@@ -398,13 +410,13 @@ impl SearchProvider for DynamicProvider {
         // Execute the command
         match self.execute_command(command, &extracted_query, use_location).await {
             Ok(response) => {
-                let action_id = utils::generate_id(&self.config.provider.id, query);
+                let action_id = utils::generate_id(&self.config.provider.id, &query.raw);
                 let r = response.clone();
                 let result = ActionResult {
                     id: action_id,
                     provider: self.config.provider.id.clone(),
                     action: ActionType::Custom { action_id: command.id.clone() },
-                    title: format!("{}: {}", self.config.provider.name, utils::truncate_text(query, 30)),
+                    title: format!("{}: {}", self.config.provider.name, utils::truncate_text(&query.raw, 30)),
                     description: response,
                     data: ActionData::Text(r),
                     metadata: ActionMetadata {
@@ -413,10 +425,12 @@ impl SearchProvider for DynamicProvider {
                         tags: vec![self.config.provider.id.clone()],
                         usage_count: 0,
                         last_used: None,
+                        desktop_id: None,
                     },
+                    secondary_actions: Vec::new(),
                 };
                 
-                Ok(vec![ScoredResult::new(result, 100, self.config.provider.id.clone())])
+                Ok(vec![ScoredResult::new(result, 1.0)])
             }
             Err(e) => {
                 utils::log_error(&format!("Dynamic provider '{}' error: {}", self.config.provider.id, e));
@@ -428,6 +442,27 @@ impl SearchProvider for DynamicProvider {
     fn configure(&mut self, _config: &crate::config::Config) {
         self.auth_failed.store(false, Ordering::Relaxed);
     }
+
+    async fn health(&self) -> ProviderHealth {
+        if !self.config.provider.enabled {
+            return ProviderHealth::warning("Disabled");
+        }
+
+        if let Some((pattern, error)) = self.invalid_patterns.first() {
+            return ProviderHealth::error(format!("Invalid matcher pattern '{}': {}", pattern, error));
+        }
+
+        if !self.check_api_key_availability() {
+            let env_var = self.config.api.api_key_env.as_deref().unwrap_or("API_KEY");
+            return ProviderHealth::error(format!("{} is not set", env_var));
+        }
+
+        if self.auth_failed.load(Ordering::Relaxed) {
+            return ProviderHealth::error("API rejected the configured key");
+        }
+
+        ProviderHealth::ok(format!("Ready (prefixes: {})", self.config.triggers.prefixes.join(", ")))
+    }
 }
 
 impl DynamicProvider {
@@ -442,6 +477,27 @@ impl DynamicProvider {
     }
 }
 
+/// Load a single dynamic provider definition
+pub fn load_provider_file(path: &Path) -> Result<DynamicProvider, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read provider config {}: {}", path.display(), e))?;
+    let config = toml::from_str::<DynamicProviderConfig>(&content)
+        .map_err(|e| format!("Failed to parse provider config {}: {}", path.display(), e))?;
+    DynamicProvider::from_config(config)
+        .map_err(|e| format!("Failed to initialize provider from {}: {}", path.display(), e))
+}
+
+/// Provider definition files in the configuration directory, in a stable order
+pub fn provider_files(config_dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(config_dir.join("providers"))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("toml"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
 /// Load all dynamic providers from the configuration directory
 pub fn load_dynamic_providers(config_dir: &Path) -> Vec<Box<dyn SearchProvider>> {
     let mut providers = Vec::new();
@@ -455,46 +511,18 @@ pub fn load_dynamic_providers(config_dir: &Path) -> Vec<Box<dyn SearchProvider>>
         }
     }
     
-    match fs::read_dir(&providers_dir) {
-        Ok(entries) => {
-            for entry in entries.flatten() {
-                let path = entry.path();
-                if path.extension().and_then(|s| s.to_str()) == Some("toml") {
-                    match fs::read_to_string(&path) {
-                        Ok(content) => {
-                            match toml::from_str::<DynamicProviderConfig>(&content) {
-                                Ok(config) => {
-                                    match DynamicProvider::from_config(config) {
-                                        Ok(provider) => {
-                                            utils::log_info(&format!(
-                                                "Loaded dynamic provider: {}", 
-                                                provider.config.provider.name
-                                            ));
-                                            providers.push(Box::new(provider) as Box<dyn SearchProvider>);
-                                        }
-                                        Err(e) => {
-                                            utils::log_error(&format!(
-                                                "Failed to initialize provider from {}: {}",
-                                                path.display(), e
-                                            ));
-                                        }
-                                    }
-                                }
-                                Err(e) => {
-                                    utils::log_error(&format!(
-                                        "Failed to parse provider config {}: {}",
-                                        path.display(), e
-                                    ));
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            utils::log_error(&format!(
-                                "Failed to read provider config {}: {}",
-                                path.display(), e
-                            ));
-                        }
+    match provider_files(config_dir) {
+        Ok(files) => {
+            for path in files {
+                match load_provider_file(&path) {
+                    Ok(provider) => {
+                        utils::log_info(&format!(
+                            "Loaded dynamic provider: {}", 
+                            provider.config.provider.name
+                        ));
+                        providers.push(Box::new(provider) as Box<dyn SearchProvider>);
                     }
+                    Err(e) => utils::log_error(&e),
                 }
             }
         }
@@ -504,4 +532,4 @@ pub fn load_dynamic_providers(config_dir: &Path) -> Vec<Box<dyn SearchProvider>>
     }
     
     providers
-}
\ No newline at end of file
+}
@@ -0,0 +1,260 @@
+// src/providers/executables.rs - Programs on $PATH that have no desktop entry
+use crate::{
+    providers::{ParsedQuery, ProviderHealth, ScoredResult, SearchProvider},
+    services::icons,
+    types::{ActionData, ActionMetadata, ActionResult, ProviderResult},
+    utils,
+};
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+const DEFAULT_PRIORITY: u8 = 30;
+const DEFAULT_MAX_RESULTS: usize = 10;
+const EXECUTABLE_ICON: &str = "application-x-executable";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Executable {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Executables found on `PATH`, along with the directory modification times they
+/// were listed at
+#[derive(Default)]
+struct PathIndex {
+    fingerprint: Vec<(PathBuf, Option<SystemTime>)>,
+    executables: Arc<Vec<Executable>>,
+}
+
+pub struct ExecutablesProvider {
+    enabled: bool,
+    priority: u8,
+    max_results: usize,
+    terminal_by_default: bool,
+    terminal_overrides: HashMap<String, bool>,
+    index: Mutex<PathIndex>,
+}
+
+impl ExecutablesProvider {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            priority: DEFAULT_PRIORITY,
+            max_results: DEFAULT_MAX_RESULTS,
+            terminal_by_default: false,
+            terminal_overrides: HashMap::new(),
+            index: Mutex::new(PathIndex::default()),
+        }
+    }
+
+    /// Executables on `PATH`, re-listed only when one of its directories changed
+    fn executables(&self) -> Arc<Vec<Executable>> {
+        let dirs = path_dirs();
+        let fingerprint: Vec<_> = dirs
+            .iter()
+            .map(|dir| (dir.clone(), dir.metadata().and_then(|m| m.modified()).ok()))
+            .collect();
+
+        let mut index = self.index.lock().unwrap_or_else(|e| e.into_inner());
+        if index.fingerprint != fingerprint || index.executables.is_empty() {
+            index.executables = Arc::new(scan_executables(&dirs));
+            index.fingerprint = fingerprint;
+            utils::log_debug(&format!("Indexed {} executables on PATH", index.executables.len()));
+        }
+        Arc::clone(&index.executables)
+    }
+
+    fn needs_terminal(&self, name: &str) -> bool {
+        self.terminal_overrides
+            .get(name)
+            .copied()
+            .unwrap_or(self.terminal_by_default)
+    }
+
+    fn result_for(&self, executable: &Executable) -> ActionResult {
+        let path = executable.path.to_string_lossy().to_string();
+        let needs_terminal = self.needs_terminal(&executable.name);
+
+        ActionResult::new_launch(
+            utils::generate_id("exec", &path),
+            self.id(),
+            executable.name.clone(),
+            "",
+            needs_terminal,
+        )
+        .with_description(path.clone())
+        .with_data(ActionData::Argv {
+            argv: vec![path],
+            working_dir: None,
+        })
+        .with_metadata(ActionMetadata {
+            icon: Some(EXECUTABLE_ICON.to_string()),
+            category: Some("executable".to_string()),
+            tags: vec!["executable".to_string()],
+            ..Default::default()
+        })
+    }
+}
+
+#[async_trait]
+impl SearchProvider for ExecutablesProvider {
+    fn id(&self) -> &'static str {
+        "executables"
+    }
+
+    fn name(&self) -> &str {
+        "Executables"
+    }
+
+    fn can_handle(&self, query: &ParsedQuery) -> bool {
+        self.enabled && !query.text.is_empty()
+    }
+
+    fn prefixes(&self) -> Vec<String> {
+        vec!["run:".to_string()]
+    }
+
+    fn priority(&self) -> u8 {
+        self.priority // Below applications, so desktop entries win for the same program
+    }
+
+    async fn search(&self, query: &ParsedQuery) -> ProviderResult<Vec<ScoredResult>> {
+        if !self.enabled || query.text.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let executables = self.executables();
+        let mut matches: Vec<ScoredResult> = executables
+            .iter()
+            .filter_map(|executable| {
+                let score = utils::calculate_relevance_score(&query.text, &executable.name, "", &[]);
+                (score > 0).then(|| {
+                    ScoredResult::new(self.result_for(executable), score as f32 / 1000.0)
+                })
+            })
+            .collect();
+
+        // Shorter names first among equal matches, so "git" beats "git-shell"
+        matches.sort_by_cached_key(|m| (std::cmp::Reverse(m.score), m.result.title.len()));
+        matches.truncate(self.max_results);
+        for m in &mut matches {
+            icons::resolve_result_icons(&mut m.result);
+        }
+
+        Ok(matches)
+    }
+
+    fn configure(&mut self, config: &crate::config::Config) {
+        let settings = &config.providers.executables;
+        self.enabled = settings.enabled;
+        self.priority = settings.priority.unwrap_or(DEFAULT_PRIORITY);
+        self.max_results = settings.max_results;
+        self.terminal_by_default = settings.terminal_by_default;
+        self.terminal_overrides = settings.terminal.clone();
+    }
+
+    async fn health(&self) -> ProviderHealth {
+        if !self.enabled {
+            return ProviderHealth::warning("Disabled in config");
+        }
+
+        match self.executables().len() {
+            0 => ProviderHealth::error("No executables found on PATH"),
+            count => ProviderHealth::ok(format!("{} executables on PATH", count)),
+        }
+    }
+}
+
+impl Default for ExecutablesProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn path_dirs() -> Vec<PathBuf> {
+    std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).filter(|dir| !dir.as_os_str().is_empty()).collect())
+        .unwrap_or_default()
+}
+
+/// List executable files in `dirs`. A name found in several directories resolves
+/// to the first, as it would in the shell.
+pub fn scan_executables(dirs: &[PathBuf]) -> Vec<Executable> {
+    let mut seen = HashSet::new();
+    let mut executables = Vec::new();
+
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+
+        let mut in_dir: Vec<Executable> = entries
+            .flatten()
+            .filter(|entry| is_executable(&entry.path()))
+            .map(|entry| Executable {
+                name: entry.file_name().to_string_lossy().to_string(),
+                path: entry.path(),
+            })
+            .collect();
+        in_dir.sort_by(|a, b| a.name.cmp(&b.name));
+
+        for executable in in_dir {
+            if seen.insert(executable.name.clone()) {
+                executables.push(executable);
+            }
+        }
+    }
+
+    executables
+}
+
+fn is_executable(path: &Path) -> bool {
+    // Follows symlinks, which is how most of /usr/bin is laid out
+    path.metadata()
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_directory_on_path_wins() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let local_bin = root.join("local-bin");
+        let usr_bin = root.join("usr-bin");
+        std::fs::create_dir_all(&local_bin).unwrap();
+        std::fs::create_dir_all(&usr_bin).unwrap();
+
+        let create = |dir: &Path, name: &str, mode: u32| {
+            let path = dir.join(name);
+            std::fs::write(&path, "#!/bin/sh\n").unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+        };
+        create(&local_bin, "deploy", 0o755);
+        create(&local_bin, "notes.txt", 0o644);
+        create(&usr_bin, "deploy", 0o755);
+        create(&usr_bin, "htop", 0o755);
+
+        let executables = scan_executables(&[local_bin.clone(), usr_bin.clone()]);
+        assert_eq!(
+            executables,
+            vec![
+                Executable { name: "deploy".to_string(), path: local_bin.join("deploy") },
+                Executable { name: "htop".to_string(), path: usr_bin.join("htop") },
+            ]
+        );
+
+        let mut provider = ExecutablesProvider::new();
+        provider.terminal_overrides.insert("htop".to_string(), true);
+        let htop = provider.result_for(&executables[1]);
+        assert!(matches!(htop.action, crate::types::ActionType::Launch { needs_terminal: true }));
+        assert!(!provider.needs_terminal("deploy"));
+    }
+}
@@ -0,0 +1,247 @@
+// src/providers/files.rs - Files below configured roots, from a persistent index
+use crate::{
+    config::FilesProviderConfig,
+    providers::{ParsedQuery, ProviderHealth, ScoredResult, SearchProvider},
+    services::{
+        file_index::{FileIndex, FileIndexSettings},
+        icons, mime,
+    },
+    types::{ActionData, ActionMetadata, ActionResult, ActionType, ProviderError, ProviderResult},
+    utils,
+};
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const PROVIDER_ID: &str = "files";
+const DEFAULT_PRIORITY: u8 = 35;
+const DEFAULT_MAX_RESULTS: usize = 10;
+/// Shorter queries match too many files to be useful
+const MIN_QUERY_LENGTH: usize = 2;
+
+pub struct FilesProvider {
+    enabled: bool,
+    priority: u8,
+    max_results: usize,
+    /// Built once the configured roots are known
+    index: Option<FileIndex>,
+}
+
+impl FilesProvider {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            priority: DEFAULT_PRIORITY,
+            max_results: DEFAULT_MAX_RESULTS,
+            index: None,
+        }
+    }
+
+    fn index_settings(settings: &FilesProviderConfig) -> FileIndexSettings {
+        FileIndexSettings {
+            roots: settings
+                .roots
+                .iter()
+                .map(|root| PathBuf::from(shellexpand::tilde(root).as_ref()))
+                .collect(),
+            max_depth: settings.max_depth,
+            include_hidden: settings.include_hidden,
+            respect_ignore_files: settings.respect_ignore_files,
+        }
+    }
+}
+
+#[async_trait]
+impl SearchProvider for FilesProvider {
+    fn id(&self) -> &'static str {
+        PROVIDER_ID
+    }
+
+    fn name(&self) -> &str {
+        "Files"
+    }
+
+    fn can_handle(&self, query: &ParsedQuery) -> bool {
+        self.enabled && query.text.chars().count() >= MIN_QUERY_LENGTH
+    }
+
+    fn prefixes(&self) -> Vec<String> {
+        vec!["file:".to_string(), "files".to_string()]
+    }
+
+    fn priority(&self) -> u8 {
+        self.priority // Below directories, which are visited far more often by name
+    }
+
+    async fn search(&self, query: &ParsedQuery) -> ProviderResult<Vec<ScoredResult>> {
+        let Some(index) = self.index.as_ref().filter(|_| self.enabled) else {
+            return Ok(Vec::new());
+        };
+        if query.text.chars().count() < MIN_QUERY_LENGTH {
+            return Ok(Vec::new());
+        }
+
+        // Matching scans the whole index, which can hold hundreds of thousands of
+        // paths, so it runs on the blocking pool like the application index refresh
+        let files = index.files();
+        let text = query.text.clone();
+        let max_results = self.max_results;
+        let matches = tokio::task::spawn_blocking(move || best_matches(&files, &text, max_results))
+            .await
+            .map_err(|e| ProviderError::Unavailable(format!("File search failed: {}", e)))?;
+
+        Ok(matches
+            .into_iter()
+            .map(|(path, score)| {
+                let mut result = file_result(&path);
+                icons::resolve_result_icons(&mut result);
+                ScoredResult::new(result, score as f32 / 1000.0)
+            })
+            .collect())
+    }
+
+    fn configure(&mut self, config: &crate::config::Config) {
+        let settings = &config.providers.files;
+        self.enabled = settings.enabled;
+        self.priority = settings.priority.unwrap_or(DEFAULT_PRIORITY);
+        self.max_results = settings.max_results;
+
+        let index_settings = Self::index_settings(settings);
+        if self.index.as_ref().is_none_or(|index| index.settings() != &index_settings) {
+            self.index = settings.enabled.then(|| {
+                FileIndex::new(
+                    &config.paths.cache_dir,
+                    index_settings,
+                    Duration::from_secs(settings.refresh_interval_secs),
+                )
+            });
+        }
+    }
+
+    fn watch(&self) {
+        // Start indexing before the first query, so it doesn't search an empty index
+        if let Some(index) = &self.index {
+            index.refresh_in_background();
+        }
+    }
+
+    async fn health(&self) -> ProviderHealth {
+        if !self.enabled {
+            return ProviderHealth::warning("Disabled in config");
+        }
+
+        match &self.index {
+            None => ProviderHealth::error("File index not configured"),
+            Some(index) => match index.files().len() {
+                0 => ProviderHealth::warning("No files indexed yet"),
+                count => ProviderHealth::ok(format!("{} files indexed", count)),
+            },
+        }
+    }
+}
+
+/// The `max_results` files whose names match `query` best, with their scores
+fn best_matches(files: &[PathBuf], query: &str, max_results: usize) -> Vec<(PathBuf, i32)> {
+    let mut matches: Vec<(&PathBuf, i32)> = files
+        .iter()
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy();
+            let score = utils::calculate_relevance_score(query, &name, "", &[]);
+            (score > 0).then_some((path, score))
+        })
+        .collect();
+
+    // Shallower paths first among equal matches, so ~/notes.md beats a copy
+    // buried in a project
+    matches.sort_by_key(|(path, score)| (std::cmp::Reverse(*score), path.as_os_str().len()));
+    matches.truncate(max_results);
+    matches.into_iter().map(|(path, score)| (path.clone(), score)).collect()
+}
+
+/// A file result that opens it with its default app, with "reveal in file
+/// manager" and "copy path" as secondary actions
+pub fn file_result(path: &Path) -> ActionResult {
+    let path_str = path.to_string_lossy().to_string();
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path_str.clone());
+    let parent = path
+        .parent()
+        .map(|parent| parent.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mime_type = mime::guess_mime_type(&path_str);
+
+    let reveal = ActionResult {
+        id: utils::generate_id("file_reveal", &path_str),
+        provider: PROVIDER_ID.to_string(),
+        action: ActionType::Open { path: parent.clone() },
+        title: "Reveal in file manager".to_string(),
+        description: parent.clone(),
+        data: ActionData::Path(parent.clone()),
+        metadata: ActionMetadata {
+            icon: Some("folder".to_string()),
+            ..Default::default()
+        },
+        secondary_actions: Vec::new(),
+    };
+    let copy_path = ActionResult::new_launch(
+        utils::generate_id("file_copy", &path_str),
+        PROVIDER_ID,
+        "Copy path",
+        "",
+        false,
+    )
+    .with_description(path_str.clone())
+    .with_data(ActionData::Argv {
+        argv: vec!["wl-copy".to_string(), "--".to_string(), path_str.clone()],
+        working_dir: None,
+    })
+    .with_metadata(ActionMetadata {
+        icon: Some("edit-copy".to_string()),
+        ..Default::default()
+    });
+
+    ActionResult {
+        id: utils::generate_id("file", &path_str),
+        provider: PROVIDER_ID.to_string(),
+        action: ActionType::Open { path: path_str.clone() },
+        title: name,
+        description: parent,
+        data: ActionData::Path(path_str),
+        metadata: ActionMetadata {
+            // Icon themes name MIME type icons like "text-plain"
+            icon: Some(mime_type.replace('/', "-")),
+            category: Some("file".to_string()),
+            tags: vec!["file".to_string(), mime_type],
+            ..Default::default()
+        },
+        secondary_actions: vec![reveal, copy_path],
+    }
+}
+
+impl Default for FilesProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_results_open_and_offer_reveal_and_copy() {
+        let result = file_result(Path::new("/home/me/docs/report.pdf"));
+
+        assert_eq!(result.title, "report.pdf");
+        assert_eq!(result.description, "/home/me/docs");
+        assert_eq!(result.open_target(), Some("/home/me/docs/report.pdf"));
+        assert_eq!(result.metadata.icon.as_deref(), Some("application-pdf"));
+
+        let reveal = &result.secondary_actions[0];
+        assert!(matches!(&reveal.action, ActionType::Open { path } if path == "/home/me/docs"));
+        let copy = &result.secondary_actions[1];
+        assert!(matches!(&copy.data, ActionData::Argv { argv, .. } if argv.last().unwrap() == "/home/me/docs/report.pdf"));
+    }
+}
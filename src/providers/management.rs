@@ -1,19 +1,25 @@
 // src/providers/management.rs
-use crate::{cli::ProviderCommands, config::get_config};
+use crate::{
+    cli::ProviderCommands,
+    config::get_config,
+    providers::{dynamic, HealthStatus, ProviderHealth, ProviderManager},
+    utils,
+};
 use anyhow::Result;
 use colored::*;
 use std::fs;
 use std::path::Path;
 
-pub fn handle_provider_command(cmd: ProviderCommands) -> Result<()> {
+pub async fn handle_provider_command(cmd: ProviderCommands) -> Result<()> {
     match cmd {
-        ProviderCommands::List => list_providers(),
+        ProviderCommands::List => list_providers().await,
         ProviderCommands::Enable { name } => enable_provider(&name),
         ProviderCommands::Disable { name } => disable_provider(&name),
         ProviderCommands::Show { name } => show_provider(&name),
         ProviderCommands::Create { name } => create_provider(&name),
         ProviderCommands::Test { name, query } => test_provider(&name, &query),
         ProviderCommands::InstallDefaults => install_default_providers(),
+        ProviderCommands::Doctor => run_doctor().await,
     }
 }
 
@@ -21,7 +27,7 @@ fn get_providers_dir() -> std::path::PathBuf {
     get_config().paths.config_dir.join("providers")
 }
 
-fn list_providers() -> Result<()> {
+async fn list_providers() -> Result<()> {
     let providers_dir = get_providers_dir();
     
     println!("{}", "Available providers:".green().bold());
@@ -54,14 +60,15 @@ fn list_providers() -> Result<()> {
     
     println!();
     println!("{}", "Built-in providers:".green().bold());
-    println!("  {} applications - Application search", "✓".green());
-    println!("  {} directories - Directory navigation (zoxide)", "✓".green());
-    
-    let ai_enabled = std::env::var("GEMINI_API_KEY").is_ok();
-    if ai_enabled {
-        println!("  {} ai - AI Assistant", "✓".green());
-    } else {
-        println!("  {} ai - AI Assistant (requires GEMINI_API_KEY)", "✗".red());
+    let mut builtin = ProviderManager::builtin();
+    builtin.configure_all(get_config());
+    for provider in builtin.providers() {
+        let health = provider.health().await;
+        if health.status == HealthStatus::Ok {
+            println!("  {} {} - {}", "✓".green(), provider.id(), provider.name());
+        } else {
+            println!("  {} {} - {} ({})", "✗".red(), provider.id().dimmed(), provider.name().dimmed(), health.message);
+        }
     }
     
     Ok(())
@@ -201,6 +208,90 @@ fn test_provider(name: &str, query: &str) -> Result<()> {
     Ok(())
 }
 
+async fn run_doctor() -> Result<()> {
+    let config = get_config();
+    let mut manager = ProviderManager::default();
+    manager.configure_all(config);
+
+    println!("{}", "Providers:".green().bold());
+    let mut checks: Vec<(String, ProviderHealth)> = Vec::new();
+    for provider in manager.providers() {
+        checks.push((provider.name().to_string(), provider.health().await));
+    }
+
+    // Definition files that never made it into the manager fail silently otherwise
+    let mut file_checks = Vec::new();
+    if let Ok(files) = dynamic::provider_files(&config.paths.config_dir) {
+        for path in files {
+            if let Err(e) = dynamic::load_provider_file(&path) {
+                let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                file_checks.push((name, ProviderHealth::error(e)));
+            }
+        }
+    }
+
+    for (name, health) in &checks {
+        print_check(name, health);
+    }
+    for (name, health) in &file_checks {
+        print_check(name, health);
+    }
+
+    println!();
+    println!("{}", "Tools:".green().bold());
+    let terminal = config
+        .general
+        .default_terminal
+        .split_whitespace()
+        .next()
+        .unwrap_or_default();
+    let tools = [
+        ("hyprctl", HealthStatus::Error, "needed to launch applications"),
+        ("rofi", HealthStatus::Warning, "needed for --rofi"),
+        (terminal, HealthStatus::Error, "needed to open directories and terminal apps"),
+    ];
+    let mut tool_checks = Vec::new();
+    for (program, missing_status, purpose) in tools {
+        let health = match utils::find_executable(program) {
+            Some(path) => ProviderHealth::ok(path.display().to_string()),
+            None => ProviderHealth {
+                status: missing_status,
+                message: format!("not found in PATH; {}", purpose),
+            },
+        };
+        print_check(program, &health);
+        tool_checks.push((program.to_string(), health));
+    }
+
+    checks.extend(file_checks);
+    checks.extend(tool_checks);
+    let failures = checks
+        .iter()
+        .filter(|(_, health)| health.status == HealthStatus::Error)
+        .count();
+    let warnings = checks
+        .iter()
+        .filter(|(_, health)| health.status == HealthStatus::Warning)
+        .count();
+
+    println!();
+    if failures > 0 {
+        anyhow::bail!("{} check(s) failed, {} warning(s)", failures, warnings);
+    }
+    println!("{} All checks passed ({} warning(s))", "✓".green(), warnings);
+
+    Ok(())
+}
+
+fn print_check(name: &str, health: &ProviderHealth) {
+    let name = format!("{:<16}", name);
+    match health.status {
+        HealthStatus::Ok => println!("  {} {} {}", "✓".green(), name, health.message),
+        HealthStatus::Warning => println!("  {} {} {}", "!".yellow(), name, health.message.yellow()),
+        HealthStatus::Error => println!("  {} {} {}", "✗".red(), name.red(), health.message.red()),
+    }
+}
+
 fn install_default_providers() -> Result<()> {
     let providers_dir = get_providers_dir();
     fs::create_dir_all(&providers_dir)?;
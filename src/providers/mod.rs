@@ -1,12 +1,26 @@
 // src/providers/mod.rs
+use crate::services::usage::LegacyIds;
 use crate::types::{ActionResult, ProviderError};
 use async_trait::async_trait;
+use futures::stream::{FuturesUnordered, Stream, StreamExt};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 pub mod ai;
 pub mod applications;
+pub mod dedup;
+pub mod desktop_entry;
 pub mod directories;
 pub mod dynamic;
+pub mod executables;
+pub mod files;
 pub mod management;
+pub mod router;
+pub mod scoring;
+pub mod windows;
+
+pub use router::{ParsedQuery, QueryRouter};
+pub use scoring::ScoreBreakdown;
 
 #[async_trait]
 pub trait SearchProvider: Send + Sync {
@@ -16,103 +30,405 @@ pub trait SearchProvider: Send + Sync {
     /// Human-readable name
     fn name(&self) -> &str;
 
-    /// Check if this provider can handle the given query
-    fn can_handle(&self, query: &str) -> bool;
+    /// Check if this provider can handle the given query. Queries scoped to another
+    /// provider by a prefix never reach this.
+    fn can_handle(&self, query: &ParsedQuery) -> bool;
+
+    /// Built-in prefix aliases that route a query to this provider (e.g. `ai:`).
+    /// Users can replace them per provider with `search.prefixes` in config.toml.
+    fn prefixes(&self) -> Vec<String> {
+        Vec::new()
+    }
 
     /// Get the priority of this provider (higher = more important)
     fn priority(&self) -> u8 {
         50 // Default priority
     }
 
-    /// Perform the search
-    async fn search(&self, query: &str) -> Result<Vec<ScoredResult>, ProviderError>;
+    /// Timeout to use when `search.provider_timeouts` has no entry for this provider.
+    /// `None` falls back to `search.provider_timeout_ms`.
+    fn default_timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Perform the search. `query.text` already has any routing prefix stripped.
+    /// Results carry a relevance between 0 and 1; `ProviderManager` turns that into
+    /// the final score, so providers shouldn't factor in usage or their priority.
+    async fn search(&self, query: &ParsedQuery) -> Result<Vec<ScoredResult>, ProviderError>;
 
     /// Optional: Provider-specific configuration
     fn configure(&mut self, _config: &crate::config::Config) {}
+
+    /// Optional: Start watching data sources for changes. Only called by interactive
+    /// interfaces that stay open across many searches.
+    fn watch(&self) {}
+
+    /// Whether the provider can currently do its job, and if not, why
+    async fn health(&self) -> ProviderHealth {
+        ProviderHealth::ok("Ready")
+    }
+
+    /// (old, new) pairs for result IDs whose scheme changed, so usage history
+    /// recorded under the old IDs can be carried over. `None` when the data the old
+    /// IDs are derived from is unavailable right now.
+    fn legacy_ids(&self) -> Option<Vec<(String, String)>> {
+        Some(Vec::new())
+    }
+
+    /// Results that open `target`, a file path or URL, with something this provider
+    /// knows about. Asked for on demand rather than attached to every result.
+    async fn open_with(&self, _target: &str) -> Vec<ActionResult> {
+        Vec::new()
+    }
+
+    /// The result that runs when `target` is opened directly, if this provider
+    /// picks one, such as the default app for a file's MIME type
+    async fn default_open(&self, _target: &str) -> Option<ActionResult> {
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HealthStatus {
+    Ok,
+    /// Disabled or degraded, but not broken
+    Warning,
+    /// Enabled but unable to return results
+    Error,
+}
+
+/// Availability of a provider, reported by `wayfindr doctor`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderHealth {
+    pub status: HealthStatus,
+    pub message: String,
+}
+
+impl ProviderHealth {
+    pub fn ok(message: impl Into<String>) -> Self {
+        Self { status: HealthStatus::Ok, message: message.into() }
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self { status: HealthStatus::Warning, message: message.into() }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self { status: HealthStatus::Error, message: message.into() }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ScoredResult {
     pub result: ActionResult,
+    /// How well the result matches the query according to its provider, 0..=1
+    pub relevance: f32,
+    /// Ranking score, highest first. Set from the relevance alone until
+    /// `ProviderManager` combines it with the other signals.
     pub score: i32,
-    pub provider_id: String,
+    /// How `score` was computed, once ranked by `ProviderManager`
+    pub breakdown: Option<ScoreBreakdown>,
 }
 
 impl ScoredResult {
-    pub fn new(result: ActionResult, score: i32, provider_id: String) -> Self {
+    pub fn new(result: ActionResult, relevance: f32) -> Self {
+        let relevance = relevance.clamp(0.0, 1.0);
         Self {
             result,
-            score,
-            provider_id,
+            relevance,
+            score: (relevance * 1000.0).round() as i32,
+            breakdown: None,
         }
     }
 }
 
+/// How a single provider's search ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProviderOutcome {
+    Success,
+    TimedOut,
+    Failed(String),
+}
+
+impl std::fmt::Display for ProviderOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProviderOutcome::Success => write!(f, "ok"),
+            ProviderOutcome::TimedOut => write!(f, "timed out"),
+            ProviderOutcome::Failed(error) => write!(f, "failed: {}", error),
+        }
+    }
+}
+
+/// Results from one provider, along with how long it took and how it ended.
+/// Timed out and failed providers produce an empty batch.
+#[derive(Debug, Clone)]
+pub struct ProviderBatch {
+    pub provider_id: String,
+    pub results: Vec<ScoredResult>,
+    pub latency: Duration,
+    pub outcome: ProviderOutcome,
+}
+
 /// Manages all search providers
 pub struct ProviderManager {
     providers: Vec<Box<dyn SearchProvider>>,
+    max_results: usize,
+    default_timeout: Duration,
+    timeout_overrides: HashMap<String, Duration>,
+    router: QueryRouter,
+    scoring: crate::config::ScoringConfig,
 }
 
 impl ProviderManager {
     pub fn new() -> Self {
         Self {
             providers: Vec::new(),
+            max_results: 50,
+            default_timeout: Duration::from_millis(1500),
+            timeout_overrides: HashMap::new(),
+            router: QueryRouter::new(),
+            scoring: crate::config::ScoringConfig::default(),
         }
     }
 
     pub fn register<P: SearchProvider + 'static>(&mut self, provider: P) {
-        self.providers.push(Box::new(provider));
+        self.register_boxed(Box::new(provider));
+    }
+
+    pub fn register_boxed(&mut self, provider: Box<dyn SearchProvider>) {
+        self.router.add_aliases(provider.id(), provider.prefixes());
+        self.providers.push(provider);
     }
 
     pub fn configure_all(&mut self, config: &crate::config::Config) {
+        self.max_results = config.general.max_results;
+        self.scoring = config.scoring.clone();
+        self.default_timeout = Duration::from_millis(config.search.provider_timeout_ms);
+        self.timeout_overrides = config
+            .search
+            .provider_timeouts
+            .iter()
+            .map(|(id, ms)| (id.clone(), Duration::from_millis(*ms)))
+            .collect();
+
         for provider in &mut self.providers {
             provider.configure(config);
         }
+
+        // Providers may derive their prefixes from config, so rebuild the router after
+        // configuring them; explicit `search.prefixes` entries replace the built-ins
+        let mut router = QueryRouter::new();
+        for provider in &self.providers {
+            match config.search.prefixes.get(provider.id()) {
+                Some(aliases) => router.set_aliases(provider.id(), aliases.iter().cloned()),
+                None => router.add_aliases(provider.id(), provider.prefixes()),
+            }
+        }
+        self.router = router;
+    }
+
+    /// Split a raw query into its routing prefix and the remaining text
+    pub fn route(&self, query: &str) -> ParsedQuery {
+        self.router.parse(query)
     }
 
+    /// Run every provider that can handle `query` concurrently and merge their results.
     pub async fn search_all(&self, query: &str) -> Vec<ScoredResult> {
+        self.search_parsed(&self.route(query)).await
+    }
+
+    /// Like `search_all` for a query that has already been routed
+    pub async fn search_parsed(&self, query: &ParsedQuery) -> Vec<ScoredResult> {
+        let batches: Vec<ProviderBatch> = self.search_stream(query).collect().await;
+        let handled_by_count = batches.len();
+
         let mut all_results = Vec::new();
-        let mut handled_by_count = 0;
+        for batch in batches {
+            self.merge_batch(&mut all_results, batch);
+        }
+
+        crate::utils::log_info(&format!("Query '{}' handled by {} providers, got {} total results", 
+            query.raw, handled_by_count, all_results.len()));
+        crate::services::icons::save_cache();
+
+        all_results
+    }
+
+    /// Run every provider that can handle `query` concurrently, yielding one batch per
+    /// provider as soon as it finishes, so callers can show fast providers' results
+    /// while slower ones are still running. A provider that fails or exceeds its
+    /// timeout only loses its own results.
+    pub fn search_stream<'a>(&'a self, query: &'a ParsedQuery) -> impl Stream<Item = ProviderBatch> + 'a {
+        self.providers
+            .iter()
+            .filter(|provider| {
+                let handles = Self::handles(provider.as_ref(), query);
+                if !handles {
+                    crate::utils::log_debug(&format!("Provider '{}' skipping query: '{}'", provider.id(), query.raw));
+                }
+                handles
+            })
+            .map(|provider| self.run_provider(provider.as_ref(), query))
+            .collect::<FuturesUnordered<_>>()
+    }
+
+    /// IDs of the providers that `search_stream` will run for `query`
+    pub fn handling_providers(&self, query: &ParsedQuery) -> Vec<String> {
+        self.providers
+            .iter()
+            .filter(|provider| Self::handles(provider.as_ref(), query))
+            .map(|provider| provider.id().to_string())
+            .collect()
+    }
+
+    /// A prefixed query only goes to the provider it was routed to
+    fn handles(provider: &dyn SearchProvider, query: &ParsedQuery) -> bool {
+        query.allows(provider.id()) && provider.can_handle(query)
+    }
+
+    /// Merge a provider batch into an already ranked result list, keeping it sorted
+    /// by score, free of duplicates and within `general.max_results`.
+    pub fn merge_batch(&self, ranked: &mut Vec<ScoredResult>, batch: ProviderBatch) {
+        ranked.extend(batch.results);
+        dedup::merge_duplicates(ranked);
+
+        // Sort by score (highest first); the sort is stable so equal scores keep arrival order
+        ranked.sort_by_key(|r| std::cmp::Reverse(r.score));
+
+        // Limit results
+        ranked.truncate(self.max_results);
+    }
+
+    async fn run_provider(&self, provider: &dyn SearchProvider, query: &ParsedQuery) -> ProviderBatch {
+        crate::utils::log_debug(&format!("Provider '{}' handling query: '{}'", provider.id(), query.text));
+
+        let timeout = self.timeout_for(provider);
+        let started = Instant::now();
+
+        let (results, outcome) = match tokio::time::timeout(timeout, provider.search(query)).await {
+            Ok(Ok(mut results)) => {
+                self.rank(provider, &mut results);
+                (results, ProviderOutcome::Success)
+            }
+            Ok(Err(e)) => (Vec::new(), ProviderOutcome::Failed(e.to_string())),
+            Err(_) => (Vec::new(), ProviderOutcome::TimedOut),
+        };
+
+        let batch = ProviderBatch {
+            provider_id: provider.id().to_string(),
+            results,
+            latency: started.elapsed(),
+            outcome,
+        };
+
+        let summary = format!(
+            "Provider '{}' {} after {}ms ({} results)",
+            batch.provider_id,
+            batch.outcome,
+            batch.latency.as_millis(),
+            batch.results.len()
+        );
+        match batch.outcome {
+            ProviderOutcome::Success => crate::utils::log_debug(&summary),
+            ProviderOutcome::TimedOut => crate::utils::log_info(&summary),
+            ProviderOutcome::Failed(_) => crate::utils::log_error(&summary),
+        }
+
+        batch
+    }
+
+    /// Replace provider scores with the combined score from `scoring::score`
+    fn rank(&self, provider: &dyn SearchProvider, results: &mut [ScoredResult]) {
+        let now = chrono::Utc::now();
+
+        for scored in results.iter_mut() {
+            let usage = crate::services::usage::get_usage_entry(&scored.result.id).map(|entry| {
+                scoring::UsageSignal {
+                    count: entry.count,
+                    last_used: entry.last_used,
+                }
+            });
+            let breakdown = scoring::score(&self.scoring, scored.relevance, provider.priority(), usage, now);
+
+            if self.scoring.debug {
+                crate::utils::log_debug(&format!("'{}' ({}): {}", scored.result.title, provider.id(), breakdown));
+                scored.result.description = format!("{} [{}]", scored.result.description, breakdown);
+            }
+
+            scored.score = breakdown.total;
+            scored.breakdown = Some(breakdown);
+        }
+    }
+
+    /// Resolve the search timeout for a provider: explicit config entry first, then the
+    /// provider's own default, then the global `search.provider_timeout_ms`.
+    fn timeout_for(&self, provider: &dyn SearchProvider) -> Duration {
+        self.timeout_overrides
+            .get(provider.id())
+            .copied()
+            .or_else(|| provider.default_timeout())
+            .unwrap_or(self.default_timeout)
+    }
 
-        // Get results from all applicable providers
+    pub fn watch_all(&self) {
         for provider in &self.providers {
-            if provider.can_handle(query) {
-                handled_by_count += 1;
-                crate::utils::log_debug(&format!("Provider '{}' handling query: '{}'", provider.id(), query));
-
-                match provider.search(query).await {
-                    Ok(mut results) => {
-                        // Apply provider priority boost
-                        let priority_boost = (provider.priority() as i32 - 50) * 10;
-                        for result in &mut results {
-                            result.score += priority_boost;
-                        }
-                        all_results.extend(results);
-                    }
-                    Err(e) => {
-                        crate::utils::log_error(&format!(
-                            "Provider '{}' failed: {}",
-                            provider.id(),
-                            e
-                        ));
-                    }
+            provider.watch();
+        }
+    }
+
+    /// Carry usage history over to the current result IDs if the usage file predates them
+    pub fn migrate_usage_ids(&self) {
+        crate::services::usage::migrate_legacy_ids(|| self.legacy_ids());
+    }
+
+    /// (old ID, new ID) pairs for every result whose ID changed with stable IDs
+    pub fn legacy_ids(&self) -> LegacyIds {
+        let mut legacy_ids = LegacyIds { renames: Vec::new(), complete: true };
+        for provider in &self.providers {
+            match provider.legacy_ids() {
+                Some(renames) => legacy_ids.renames.extend(renames),
+                None => {
+                    crate::utils::log_warn(&format!("Provider '{}' can't list its old result IDs yet", provider.id()));
+                    legacy_ids.complete = false;
                 }
-            } else {
-                crate::utils::log_debug(&format!("Provider '{}' skipping query: '{}'", provider.id(), query));
             }
         }
+        legacy_ids
+    }
 
-        crate::utils::log_info(&format!("Query '{}' handled by {} providers, got {} total results", 
-            query, handled_by_count, all_results.len()));
+    /// Ways to open `target` offered by any provider, for results that point at a
+    /// file, directory or URL
+    pub async fn open_with(&self, target: &str) -> Vec<ActionResult> {
+        let mut results = Vec::new();
+        for provider in &self.providers {
+            results.extend(provider.open_with(target).await);
+        }
+        results
+    }
 
-        // Sort by score (highest first)
-        all_results.sort_by(|a, b| b.score.cmp(&a.score));
+    /// What opening `target` directly runs, from the first provider that picks something
+    pub async fn default_open(&self, target: &str) -> Option<ActionResult> {
+        for provider in &self.providers {
+            if let Some(result) = provider.default_open(target).await {
+                return Some(result);
+            }
+        }
+        None
+    }
 
-        // Limit results
-        let max_results = crate::config::get_config().general.max_results;
-        all_results.truncate(max_results);
+    /// A result's own secondary actions, followed by the ways any provider offers
+    /// to open its target
+    pub async fn secondary_actions(&self, result: &ActionResult) -> Vec<ActionResult> {
+        let mut actions = result.secondary_actions.clone();
+        if let Some(target) = result.open_target() {
+            actions.extend(self.open_with(target).await);
+        }
+        actions
+    }
 
-        all_results
+    pub fn providers(&self) -> impl Iterator<Item = &dyn SearchProvider> {
+        self.providers.iter().map(|p| p.as_ref())
     }
 
     pub fn get_provider(&self, id: &str) -> Option<&dyn SearchProvider> {
@@ -123,20 +439,29 @@ impl ProviderManager {
     }
 }
 
-impl Default for ProviderManager {
-    fn default() -> Self {
+impl ProviderManager {
+    /// A manager with only the built-in providers, unconfigured
+    pub fn builtin() -> Self {
         let mut manager = Self::new();
-        let config = crate::config::get_config();
-
-        // Register built-in providers
         manager.register(applications::ApplicationProvider::new());
         manager.register(directories::DirectoryProvider::new());
+        manager.register(files::FilesProvider::new());
+        manager.register(executables::ExecutablesProvider::new());
+        manager.register(windows::WindowProvider::new());
         manager.register(ai::AiProvider::new());
+        manager
+    }
+}
+
+impl Default for ProviderManager {
+    fn default() -> Self {
+        let mut manager = Self::builtin();
+        let config = crate::config::get_config();
 
         // Load and register dynamic providers
         let dynamic_providers = dynamic::load_dynamic_providers(&config.paths.config_dir);
         for provider in dynamic_providers {
-            manager.providers.push(provider);
+            manager.register_boxed(provider);
         }
 
         crate::utils::log_info(&format!(
@@ -146,4 +471,179 @@ impl Default for ProviderManager {
 
         manager
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct SleepyProvider {
+        id: &'static str,
+        delay: Duration,
+        timeout: Duration,
+    }
+
+    #[async_trait]
+    impl SearchProvider for SleepyProvider {
+        fn id(&self) -> &'static str {
+            self.id
+        }
+
+        fn name(&self) -> &str {
+            self.id
+        }
+
+        fn can_handle(&self, _query: &ParsedQuery) -> bool {
+            true
+        }
+
+        fn default_timeout(&self) -> Option<Duration> {
+            Some(self.timeout)
+        }
+
+        async fn search(&self, query: &ParsedQuery) -> Result<Vec<ScoredResult>, ProviderError> {
+            tokio::time::sleep(self.delay).await;
+            let result = ActionResult::new_launch(self.id, self.id, &query.text, self.id, false);
+            Ok(vec![ScoredResult::new(result, 1.0)])
+        }
+
+        async fn open_with(&self, target: &str) -> Vec<ActionResult> {
+            vec![ActionResult::new_launch(self.id, self.id, target, self.id, false)]
+        }
+    }
+
+    fn sleepy(id: &'static str, delay_ms: u64, timeout_ms: u64) -> SleepyProvider {
+        SleepyProvider {
+            id,
+            delay: Duration::from_millis(delay_ms),
+            timeout: Duration::from_millis(timeout_ms),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_stream_drops_only_timed_out_provider() {
+        let mut manager = ProviderManager::new();
+        manager.register(sleepy("test_fast", 10, 1000));
+        manager.register(sleepy("test_hanging", 5000, 50));
+
+        let query = manager.route("query");
+        let batches: Vec<ProviderBatch> = manager.search_stream(&query).collect().await;
+        assert_eq!(batches.len(), 2);
+
+        let fast = batches.iter().find(|b| b.provider_id == "test_fast").unwrap();
+        assert_eq!(fast.outcome, ProviderOutcome::Success);
+        assert_eq!(fast.results.len(), 1);
+
+        let hanging = batches.iter().find(|b| b.provider_id == "test_hanging").unwrap();
+        assert_eq!(hanging.outcome, ProviderOutcome::TimedOut);
+        assert!(hanging.results.is_empty());
+        assert!(hanging.latency < Duration::from_millis(1000));
+    }
+
+    #[tokio::test]
+    async fn test_prefixed_query_only_reaches_scoped_provider() {
+        let mut manager = ProviderManager::new();
+        manager.register(sleepy("test_a", 0, 1000));
+        manager.register(sleepy("test_b", 0, 1000));
+        manager.router.add_aliases("test_b", ["b:".to_string()]);
+
+        let parsed = manager.route("b: hello");
+        assert_eq!(manager.handling_providers(&parsed), vec!["test_b"]);
+
+        let results = manager.search_all("b: hello").await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].result.provider, "test_b");
+        assert_eq!(results[0].result.title, "hello");
+
+        assert_eq!(manager.search_all("hello").await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_secondary_actions_are_followed_by_open_with() {
+        let mut manager = ProviderManager::new();
+        manager.register(sleepy("test_opener", 0, 1000));
+
+        let result = files::file_result(std::path::Path::new("/tmp/notes.txt"));
+        let titles: Vec<String> = manager
+            .secondary_actions(&result)
+            .await
+            .into_iter()
+            .map(|action| action.title)
+            .collect();
+        assert_eq!(titles, ["Reveal in file manager", "Copy path", "/tmp/notes.txt"]);
+    }
+
+    #[tokio::test]
+    async fn test_search_all_runs_providers_concurrently() {
+        let mut manager = ProviderManager::new();
+        manager.register(sleepy("test_a", 200, 1000));
+        manager.register(sleepy("test_b", 200, 1000));
+        manager.register(sleepy("test_c", 200, 1000));
+
+        let started = Instant::now();
+        let results = manager.search_all("query").await;
+
+        assert_eq!(results.len(), 3);
+        assert!(started.elapsed() < Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn test_search_stream_yields_fast_providers_first() {
+        let mut manager = ProviderManager::new();
+        manager.register(sleepy("test_slow", 200, 1000));
+        manager.register(sleepy("test_quick", 10, 1000));
+
+        let query = ParsedQuery::unscoped("query");
+        let order: Vec<String> = manager
+            .search_stream(&query)
+            .map(|batch| batch.provider_id)
+            .collect()
+            .await;
+
+        assert_eq!(order, vec!["test_quick", "test_slow"]);
+    }
+
+    #[tokio::test]
+    async fn test_dropping_search_stream_cancels_pending_providers() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        struct FlagProvider(Arc<AtomicBool>);
+
+        #[async_trait]
+        impl SearchProvider for FlagProvider {
+            fn id(&self) -> &'static str {
+                "test_flag"
+            }
+
+            fn name(&self) -> &str {
+                "Flag"
+            }
+
+            fn can_handle(&self, _query: &ParsedQuery) -> bool {
+                true
+            }
+
+            async fn search(&self, _query: &ParsedQuery) -> Result<Vec<ScoredResult>, ProviderError> {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                self.0.store(true, Ordering::SeqCst);
+                Ok(Vec::new())
+            }
+        }
+
+        let finished = Arc::new(AtomicBool::new(false));
+        let mut manager = ProviderManager::new();
+        manager.register(sleepy("test_quick", 10, 1000));
+        manager.register(FlagProvider(finished.clone()));
+
+        let query = ParsedQuery::unscoped("query");
+        {
+            let mut batches = manager.search_stream(&query);
+            let first = batches.next().await.unwrap();
+            assert_eq!(first.provider_id, "test_quick");
+        }
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(!finished.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,174 @@
+// src/providers/router.rs - Prefix routing shared by every provider
+use std::collections::HashMap;
+
+/// A query after prefix routing. Providers receive this instead of the raw input so
+/// none of them has to know about other providers' prefixes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedQuery {
+    /// The query exactly as typed
+    pub raw: String,
+    /// Provider the query was routed to by a prefix, if any
+    pub scope: Option<String>,
+    /// The prefix alias that matched, if any
+    pub prefix: Option<String>,
+    /// The query with any prefix stripped and surrounding whitespace trimmed
+    pub text: String,
+}
+
+impl ParsedQuery {
+    /// A query that isn't routed to any particular provider
+    pub fn unscoped(text: &str) -> Self {
+        Self {
+            raw: text.to_string(),
+            scope: None,
+            prefix: None,
+            text: text.trim().to_string(),
+        }
+    }
+
+    /// A query routed to `provider_id` without going through a prefix
+    pub fn scoped(provider_id: &str, text: &str) -> Self {
+        Self {
+            raw: text.to_string(),
+            scope: Some(provider_id.to_string()),
+            prefix: None,
+            text: text.trim().to_string(),
+        }
+    }
+
+    pub fn is_scoped(&self) -> bool {
+        self.scope.is_some()
+    }
+
+    pub fn is_scoped_to(&self, provider_id: &str) -> bool {
+        self.scope.as_deref() == Some(provider_id)
+    }
+
+    /// Whether this provider may handle the query at all: either it's unscoped or
+    /// it's scoped to this provider
+    pub fn allows(&self, provider_id: &str) -> bool {
+        self.scope.is_none() || self.is_scoped_to(provider_id)
+    }
+}
+
+/// Maps prefix aliases such as `ai:` or `apps` to provider ids.
+///
+/// An alias matches when the query starts with it. Aliases ending in a letter or digit
+/// (keywords like `apps`) must also be followed by whitespace or the end of the query,
+/// so `apps` doesn't swallow `appstream`. When several aliases match, the longest wins.
+#[derive(Debug, Clone, Default)]
+pub struct QueryRouter {
+    aliases: HashMap<String, Vec<String>>,
+}
+
+impl QueryRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add aliases for a provider on top of any it already has
+    pub fn add_aliases(&mut self, provider_id: &str, aliases: impl IntoIterator<Item = String>) {
+        let entry = self.aliases.entry(provider_id.to_string()).or_default();
+        for alias in aliases {
+            if !alias.is_empty() && !entry.contains(&alias) {
+                entry.push(alias);
+            }
+        }
+    }
+
+    /// Replace all aliases for a provider
+    pub fn set_aliases(&mut self, provider_id: &str, aliases: impl IntoIterator<Item = String>) {
+        self.aliases.remove(provider_id);
+        self.add_aliases(provider_id, aliases);
+    }
+
+    pub fn parse(&self, raw: &str) -> ParsedQuery {
+        let input = raw.trim_start();
+
+        let best = self
+            .aliases
+            .iter()
+            .flat_map(|(provider_id, aliases)| aliases.iter().map(move |alias| (provider_id, alias)))
+            .filter(|(_, alias)| Self::alias_matches(input, alias))
+            .max_by_key(|(_, alias)| alias.len());
+
+        match best {
+            Some((provider_id, alias)) => ParsedQuery {
+                raw: raw.to_string(),
+                scope: Some(provider_id.clone()),
+                prefix: Some(alias.clone()),
+                text: input[alias.len()..].trim().to_string(),
+            },
+            None => ParsedQuery::unscoped(raw),
+        }
+    }
+
+    fn alias_matches(input: &str, alias: &str) -> bool {
+        let Some(rest) = input.strip_prefix(alias) else {
+            return false;
+        };
+
+        let is_keyword = alias.chars().last().is_some_and(char::is_alphanumeric);
+        !is_keyword || rest.is_empty() || rest.starts_with(char::is_whitespace)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn router() -> QueryRouter {
+        let mut router = QueryRouter::new();
+        router.add_aliases("ai", ["ai:".to_string(), "ask:".to_string()]);
+        router.add_aliases("applications", ["app:".to_string(), "apps".to_string()]);
+        router.add_aliases("weather", ["w:".to_string(), "weather:".to_string()]);
+        router.add_aliases("calc", ["=".to_string()]);
+        router
+    }
+
+    #[test]
+    fn test_prefix_is_stripped() {
+        let parsed = router().parse("ai: what is rust");
+        assert_eq!(parsed.scope.as_deref(), Some("ai"));
+        assert_eq!(parsed.prefix.as_deref(), Some("ai:"));
+        assert_eq!(parsed.text, "what is rust");
+
+        let parsed = router().parse("=2+2");
+        assert_eq!(parsed.scope.as_deref(), Some("calc"));
+        assert_eq!(parsed.text, "2+2");
+    }
+
+    #[test]
+    fn test_keyword_requires_word_boundary() {
+        let parsed = router().parse("apps");
+        assert!(parsed.is_scoped_to("applications"));
+        assert_eq!(parsed.text, "");
+
+        let parsed = router().parse("apps fire");
+        assert!(parsed.is_scoped_to("applications"));
+        assert_eq!(parsed.text, "fire");
+
+        let parsed = router().parse("appstream");
+        assert!(!parsed.is_scoped());
+        assert_eq!(parsed.text, "appstream");
+    }
+
+    #[test]
+    fn test_longest_alias_wins() {
+        let mut router = router();
+        router.add_aliases("wiki", ["weather:x".to_string()]);
+        assert!(router.parse("weather: paris").is_scoped_to("weather"));
+        assert!(router.parse("weather:x paris").is_scoped_to("wiki"));
+    }
+
+    #[test]
+    fn test_set_aliases_replaces_defaults() {
+        let mut router = router();
+        router.set_aliases("ai", ["?".to_string()]);
+
+        assert!(!router.parse("ai: hello").is_scoped());
+        let parsed = router.parse("? hello");
+        assert!(parsed.is_scoped_to("ai"));
+        assert_eq!(parsed.text, "hello");
+    }
+}
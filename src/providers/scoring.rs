@@ -0,0 +1,125 @@
+// src/providers/scoring.rs - Combines provider relevance with usage into one ranking
+use crate::config::ScoringConfig;
+use chrono::{DateTime, Utc};
+
+/// Final scores are on a 0..=1000 scale
+const SCORE_SCALE: f32 = 1000.0;
+
+/// How a result's final score was put together. Each field is that signal's
+/// contribution in points, so they add up to `total`.
+///
+/// ```text
+/// score = 1000 * ( relevance_weight * relevance
+///                + provider_weight  * priority / 100
+///                + usage_weight     * usage
+///                + recency_weight   * recency )
+///
+/// relevance  provider-reported, 0..=1
+/// priority   provider priority, 0..=100
+/// usage      ln(1 + count) / ln(1 + usage_saturation), capped at 1
+/// recency    0.5 ^ (days since last use / recency_half_life_days)
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ScoreBreakdown {
+    pub relevance: f32,
+    pub provider: f32,
+    pub usage: f32,
+    pub recency: f32,
+    pub total: i32,
+}
+
+impl std::fmt::Display for ScoreBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "score {} = relevance {:.0} + provider {:.0} + usage {:.0} + recency {:.0}",
+            self.total, self.relevance, self.provider, self.usage, self.recency
+        )
+    }
+}
+
+/// Usage history for a single result
+#[derive(Debug, Clone, Copy)]
+pub struct UsageSignal {
+    pub count: u32,
+    pub last_used: DateTime<Utc>,
+}
+
+pub fn score(
+    config: &ScoringConfig,
+    relevance: f32,
+    priority: u8,
+    usage: Option<UsageSignal>,
+    now: DateTime<Utc>,
+) -> ScoreBreakdown {
+    let relevance = relevance.clamp(0.0, 1.0);
+    let provider = (priority as f32 / 100.0).min(1.0);
+    let (usage_signal, recency_signal) = match usage {
+        Some(usage) if usage.count > 0 => (
+            usage_signal(usage.count, config.usage_saturation),
+            recency_signal(usage.last_used, now, config.recency_half_life_days),
+        ),
+        _ => (0.0, 0.0),
+    };
+
+    let mut breakdown = ScoreBreakdown {
+        relevance: SCORE_SCALE * config.relevance_weight * relevance,
+        provider: SCORE_SCALE * config.provider_weight * provider,
+        usage: SCORE_SCALE * config.usage_weight * usage_signal,
+        recency: SCORE_SCALE * config.recency_weight * recency_signal,
+        total: 0,
+    };
+    breakdown.total =
+        (breakdown.relevance + breakdown.provider + breakdown.usage + breakdown.recency).round() as i32;
+    breakdown
+}
+
+fn usage_signal(count: u32, saturation: u32) -> f32 {
+    let saturation = saturation.max(1) as f32;
+    ((1.0 + count as f32).ln() / (1.0 + saturation).ln()).min(1.0)
+}
+
+fn recency_signal(last_used: DateTime<Utc>, now: DateTime<Utc>, half_life_days: f32) -> f32 {
+    if half_life_days <= 0.0 {
+        return 0.0;
+    }
+    let age_days = (now - last_used).num_seconds().max(0) as f32 / 86_400.0;
+    0.5_f32.powf(age_days / half_life_days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contributions_add_up_to_total() {
+        let config = ScoringConfig::default();
+        let now = Utc::now();
+        let usage = UsageSignal { count: 10, last_used: now - chrono::Duration::days(7) };
+
+        let breakdown = score(&config, 0.8, 70, Some(usage), now);
+        let sum = breakdown.relevance + breakdown.provider + breakdown.usage + breakdown.recency;
+        assert_eq!(breakdown.total, sum.round() as i32);
+
+        // One half-life ago halves the recency signal
+        let expected_recency = SCORE_SCALE * config.recency_weight * 0.5;
+        assert!((breakdown.recency - expected_recency).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_usage_breaks_ties_but_relevance_dominates() {
+        let config = ScoringConfig::default();
+        let now = Utc::now();
+        let heavy_use = Some(UsageSignal { count: 500, last_used: now });
+
+        let used_partial = score(&config, 0.6, 70, heavy_use, now);
+        let unused_partial = score(&config, 0.6, 70, None, now);
+        assert!(used_partial.total > unused_partial.total);
+
+        // Usage saturates, so it can't push a weak match past an exact one
+        let unused_exact = score(&config, 1.0, 70, None, now);
+        let used_fuzzy = score(&config, 0.2, 70, heavy_use, now);
+        assert!(unused_exact.total > used_fuzzy.total);
+        assert!(score(&config, 1.0, 100, heavy_use, now).total <= 1000);
+    }
+}
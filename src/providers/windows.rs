@@ -0,0 +1,446 @@
+// src/providers/windows.rs - Switching to open Hyprland windows
+use crate::{
+    providers::{ParsedQuery, ProviderHealth, ScoredResult, SearchProvider},
+    types::{ActionData, ActionMetadata, ActionResult, ActionType, ProviderError, ProviderResult},
+    utils,
+};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const DEFAULT_PRIORITY: u8 = 60;
+const DEFAULT_MAX_RESULTS: usize = 15;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Workspace {
+    pub id: i64,
+    pub name: String,
+}
+
+/// A client as reported by `hyprctl clients -j`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Window {
+    pub address: String,
+    #[serde(default = "default_true")]
+    pub mapped: bool,
+    #[serde(default)]
+    pub hidden: bool,
+    pub workspace: Workspace,
+    pub class: String,
+    pub title: String,
+    #[serde(default)]
+    pub initial_class: String,
+    #[serde(default)]
+    pub pid: i64,
+    /// 0 for the focused window, counting up for less recently focused ones
+    #[serde(default, rename = "focusHistoryID")]
+    pub focus_history_id: i64,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Window {
+    /// Whether the window belongs to an app known by any of `keys` (window classes,
+    /// desktop file IDs or program names, compared case-insensitively)
+    pub fn belongs_to(&self, keys: &[String]) -> bool {
+        keys.iter().any(|key| {
+            key.eq_ignore_ascii_case(&self.class) || key.eq_ignore_ascii_case(&self.initial_class)
+        })
+    }
+}
+
+/// Source of open windows and the means to focus one, so the provider can run
+/// against fixture output instead of a live compositor
+#[async_trait]
+pub trait WindowBackend: Send + Sync {
+    /// Open windows, most recently focused first
+    async fn windows(&self) -> ProviderResult<Vec<Window>>;
+
+    async fn focus(&self, address: &str) -> ProviderResult<()>;
+}
+
+/// Parse `hyprctl clients -j`, dropping unmapped and hidden clients
+pub fn parse_clients(json: &str) -> ProviderResult<Vec<Window>> {
+    let mut windows: Vec<Window> = serde_json::from_str(json)
+        .map_err(|e| ProviderError::Parsing(format!("Invalid hyprctl clients output: {}", e)))?;
+    windows.retain(|window| window.mapped && !window.hidden && !window.address.is_empty());
+    windows.sort_by_key(|window| window.focus_history_id);
+    Ok(windows)
+}
+
+/// `WindowBackend` on top of `hyprctl`
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HyprctlBackend;
+
+impl HyprctlBackend {
+    /// Whether we're inside a Hyprland session at all
+    pub fn is_available() -> bool {
+        std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some()
+    }
+}
+
+#[async_trait]
+impl WindowBackend for HyprctlBackend {
+    async fn windows(&self) -> ProviderResult<Vec<Window>> {
+        let output = tokio::process::Command::new("hyprctl")
+            .args(["clients", "-j"])
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| ProviderError::Command(format!("Failed to run hyprctl: {}", e)))?;
+
+        if !output.status.success() {
+            return Err(ProviderError::Command(format!(
+                "hyprctl clients failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+
+        parse_clients(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    async fn focus(&self, address: &str) -> ProviderResult<()> {
+        let output = tokio::process::Command::new("hyprctl")
+            .args(["dispatch", "focuswindow", &format!("address:{}", address)])
+            .output()
+            .await
+            .map_err(|e| ProviderError::Command(format!("Failed to run hyprctl: {}", e)))?;
+
+        // hyprctl reports dispatch errors on stdout with a zero exit status
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        if !output.status.success() || !stdout.trim().eq_ignore_ascii_case("ok") {
+            return Err(ProviderError::Command(format!("focuswindow failed: {}", stdout.trim())));
+        }
+        Ok(())
+    }
+}
+
+/// Reuses another backend's window list for `ttl`, for callers that would
+/// otherwise list windows on every keystroke
+pub struct CachedBackend {
+    inner: Arc<dyn WindowBackend>,
+    ttl: Duration,
+    listing: Mutex<Option<(Instant, Vec<Window>)>>,
+}
+
+impl CachedBackend {
+    pub fn new(inner: Arc<dyn WindowBackend>, ttl: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            listing: Mutex::new(None),
+        }
+    }
+
+    fn invalidate(&self) {
+        if let Ok(mut listing) = self.listing.lock() {
+            *listing = None;
+        }
+    }
+}
+
+#[async_trait]
+impl WindowBackend for CachedBackend {
+    async fn windows(&self) -> ProviderResult<Vec<Window>> {
+        let cached = self.listing.lock().ok().and_then(|listing| {
+            listing
+                .as_ref()
+                .filter(|(listed_at, _)| listed_at.elapsed() < self.ttl)
+                .map(|(_, windows)| windows.clone())
+        });
+        if let Some(windows) = cached {
+            return Ok(windows);
+        }
+
+        let windows = self.inner.windows().await?;
+        if let Ok(mut listing) = self.listing.lock() {
+            *listing = Some((Instant::now(), windows.clone()));
+        }
+        Ok(windows)
+    }
+
+    async fn focus(&self, address: &str) -> ProviderResult<()> {
+        // Focusing reorders the focus history
+        self.invalidate();
+        self.inner.focus(address).await
+    }
+}
+
+/// A result that focuses `window`
+pub fn focus_result(window: &Window, provider_id: &str) -> ActionResult {
+    ActionResult {
+        id: utils::generate_id("window", &window.address),
+        provider: provider_id.to_string(),
+        action: ActionType::Focus {
+            address: window.address.clone(),
+        },
+        title: window.title.clone(),
+        description: format!("{} on workspace {}", window.class, window.workspace.name),
+        data: ActionData::Text(window.address.clone()),
+        metadata: ActionMetadata {
+            icon: Some(window.class.to_lowercase()),
+            category: Some("window".to_string()),
+            tags: vec!["window".to_string(), window.class.clone()],
+            usage_count: 0,
+            last_used: None,
+            desktop_id: None,
+        },
+        secondary_actions: Vec::new(),
+    }
+}
+
+/// Turn a launch result into one that focuses the app's most recent window. The
+/// original launch stays available as a secondary action for a new instance.
+pub fn offer_focus_existing(result: &mut ActionResult, app_keys: &[String], windows: &[Window]) {
+    if !matches!(result.action, ActionType::Launch { .. }) {
+        return;
+    }
+    let Some(window) = windows.iter().find(|window| window.belongs_to(app_keys)) else {
+        return;
+    };
+
+    let mut new_instance = result.clone();
+    new_instance.id = format!("{}:new", result.id);
+    new_instance.title = format!("{}: New instance", result.title);
+    new_instance.secondary_actions = Vec::new();
+
+    result.action = ActionType::Focus {
+        address: window.address.clone(),
+    };
+    result.description = format!("Focus \"{}\" on workspace {}", window.title, window.workspace.name);
+    result.secondary_actions.insert(0, new_instance);
+}
+
+pub struct WindowProvider {
+    backend: Arc<dyn WindowBackend>,
+    enabled: bool,
+    priority: u8,
+    max_results: usize,
+}
+
+impl WindowProvider {
+    pub fn new() -> Self {
+        Self::with_backend(Arc::new(HyprctlBackend))
+    }
+
+    pub fn with_backend(backend: Arc<dyn WindowBackend>) -> Self {
+        Self {
+            backend,
+            enabled: HyprctlBackend::is_available(),
+            priority: DEFAULT_PRIORITY,
+            max_results: DEFAULT_MAX_RESULTS,
+        }
+    }
+
+    fn relevance(window: &Window, query: &str) -> f32 {
+        let by_class = utils::relevance(query, &window.class, "", &[]);
+        let by_title = utils::relevance(query, &window.title, "", &[]) * 0.9;
+        by_class.max(by_title)
+    }
+}
+
+#[async_trait]
+impl SearchProvider for WindowProvider {
+    fn id(&self) -> &'static str {
+        "windows"
+    }
+
+    fn name(&self) -> &str {
+        "Windows"
+    }
+
+    fn can_handle(&self, query: &ParsedQuery) -> bool {
+        // Listing every window is only useful when asked for with the prefix
+        self.enabled && (!query.text.is_empty() || query.is_scoped_to(self.id()))
+    }
+
+    fn prefixes(&self) -> Vec<String> {
+        vec!["win:".to_string(), "windows".to_string()]
+    }
+
+    fn priority(&self) -> u8 {
+        self.priority
+    }
+
+    async fn search(&self, query: &ParsedQuery) -> ProviderResult<Vec<ScoredResult>> {
+        if !self.enabled {
+            return Ok(Vec::new());
+        }
+
+        let windows = self.backend.windows().await?;
+        let mut matches: Vec<ScoredResult> = windows
+            .iter()
+            .enumerate()
+            .filter_map(|(rank, window)| {
+                let relevance = if query.text.is_empty() {
+                    // Most recently focused first
+                    0.5 / (1.0 + rank as f32)
+                } else {
+                    Self::relevance(window, &query.text)
+                };
+                (relevance > 0.0).then(|| ScoredResult::new(focus_result(window, self.id()), relevance))
+            })
+            .collect();
+
+        matches.sort_by_key(|m| std::cmp::Reverse(m.score));
+        matches.truncate(self.max_results);
+        Ok(matches)
+    }
+
+    fn configure(&mut self, config: &crate::config::Config) {
+        let settings = &config.providers.windows;
+        self.enabled = settings.enabled && HyprctlBackend::is_available();
+        self.priority = settings.priority.unwrap_or(DEFAULT_PRIORITY);
+        self.max_results = settings.max_results;
+    }
+
+    async fn health(&self) -> ProviderHealth {
+        if !HyprctlBackend::is_available() {
+            ProviderHealth::warning("Not running under Hyprland")
+        } else if !self.enabled {
+            ProviderHealth::warning("Disabled in config")
+        } else if utils::find_executable("hyprctl").is_none() {
+            ProviderHealth::error("hyprctl not found")
+        } else {
+            ProviderHealth::ok("Listing Hyprland windows")
+        }
+    }
+}
+
+impl Default for WindowProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::ProviderManager;
+    use crate::services::ExecutionService;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const CLIENTS_FIXTURE: &str = r#"[
+        {
+            "address": "0x5e1a70",
+            "mapped": true,
+            "hidden": false,
+            "at": [10, 40],
+            "size": [1900, 1030],
+            "workspace": { "id": 2, "name": "2" },
+            "floating": false,
+            "monitor": 0,
+            "class": "firefox",
+            "title": "Rust docs - Mozilla Firefox",
+            "initialClass": "firefox",
+            "initialTitle": "Mozilla Firefox",
+            "pid": 4242,
+            "xwayland": false,
+            "pinned": false,
+            "fullscreen": 0,
+            "focusHistoryID": 1
+        },
+        {
+            "address": "0x5e2b80",
+            "mapped": true,
+            "hidden": false,
+            "workspace": { "id": 1, "name": "1" },
+            "class": "kitty",
+            "title": "~/src/wayfindr",
+            "initialClass": "kitty",
+            "pid": 4343,
+            "focusHistoryID": 0
+        },
+        {
+            "address": "0x5e3c90",
+            "mapped": false,
+            "hidden": false,
+            "workspace": { "id": -99, "name": "special:scratch" },
+            "class": "pavucontrol",
+            "title": "Volume Control",
+            "pid": 4444,
+            "focusHistoryID": 2
+        }
+    ]"#;
+
+    #[derive(Default)]
+    struct FixtureBackend {
+        listings: AtomicUsize,
+        focused: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl WindowBackend for FixtureBackend {
+        async fn windows(&self) -> ProviderResult<Vec<Window>> {
+            self.listings.fetch_add(1, Ordering::SeqCst);
+            parse_clients(CLIENTS_FIXTURE)
+        }
+
+        async fn focus(&self, address: &str) -> ProviderResult<()> {
+            self.focused.lock().unwrap().push(address.to_string());
+            Ok(())
+        }
+    }
+
+    fn provider(backend: Arc<FixtureBackend>) -> WindowProvider {
+        let mut provider = WindowProvider::with_backend(backend);
+        provider.enabled = true;
+        provider
+    }
+
+    #[tokio::test]
+    async fn test_windows_are_listed_by_class_and_focused_through_the_backend() {
+        let backend = Arc::new(FixtureBackend::default());
+        let provider = provider(Arc::clone(&backend));
+
+        let results = provider.search(&ParsedQuery::unscoped("rust docs")).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].result.title, "Rust docs - Mozilla Firefox");
+        assert_eq!(results[0].result.description, "firefox on workspace 2");
+
+        // The bare prefix lists mapped windows by focus history
+        let all = provider.search(&ParsedQuery::scoped("windows", "")).await.unwrap();
+        let classes: Vec<_> = all.iter().map(|r| r.result.description.as_str()).collect();
+        assert_eq!(classes, vec!["kitty on workspace 1", "firefox on workspace 2"]);
+
+        let execution = ExecutionService::with_window_backend(Arc::new(ProviderManager::new()), backend.clone());
+        let exits = execution.execute(&results[0].result).await.unwrap();
+        assert!(exits);
+        assert_eq!(*backend.focused.lock().unwrap(), vec!["0x5e1a70"]);
+    }
+
+    #[tokio::test]
+    async fn test_cached_backend_reuses_recent_listings() {
+        let backend = Arc::new(FixtureBackend::default());
+        let cached = CachedBackend::new(backend.clone(), Duration::from_secs(60));
+
+        assert_eq!(cached.windows().await.unwrap().len(), 2);
+        assert_eq!(cached.windows().await.unwrap().len(), 2);
+        assert_eq!(backend.listings.load(Ordering::SeqCst), 1);
+
+        cached.focus("0x5e2b80").await.unwrap();
+        cached.windows().await.unwrap();
+        assert_eq!(backend.listings.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_launch_results_offer_focus_existing() {
+        let windows = parse_clients(CLIENTS_FIXTURE).unwrap();
+        let keys = vec!["firefox".to_string(), "firefox.desktop".to_string()];
+
+        let mut firefox = ActionResult::new_launch("app:firefox.desktop", "applications", "Firefox", "firefox", false);
+        offer_focus_existing(&mut firefox, &keys, &windows);
+        assert!(matches!(&firefox.action, ActionType::Focus { address } if address == "0x5e1a70"));
+        assert_eq!(firefox.secondary_actions[0].title, "Firefox: New instance");
+        assert!(matches!(firefox.secondary_actions[0].action, ActionType::Launch { .. }));
+
+        // Unmapped windows don't count
+        let mut volume = ActionResult::new_launch("app:pavucontrol.desktop", "applications", "Volume", "pavucontrol", false);
+        offer_focus_existing(&mut volume, &["pavucontrol".to_string()], &windows);
+        assert!(matches!(volume.action, ActionType::Launch { .. }));
+    }
+}
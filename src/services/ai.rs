@@ -54,12 +54,14 @@ struct GeminiResponsePart {
 #[derive(Deserialize, Debug)]
 struct GeminiResponseContent {
     parts: Vec<GeminiResponsePart>,
+    #[allow(dead_code, reason = "mirrors the Gemini response shape")]
     role: String,
 }
 
 #[derive(Deserialize, Debug)]
 struct GeminiCandidate {
     content: GeminiResponseContent,
+    #[allow(dead_code, reason = "mirrors the Gemini response shape")]
     #[serde(rename = "finishReason")]
     finish_reason: Option<String>,
 }
@@ -106,7 +108,7 @@ pub async fn query_gemini_api(prompt: String) -> Result<String, AiError> {
     }
 
     let response_text = res.text().await?;
-    utils::log_debug(&format!("Received response from Gemini API"));
+    utils::log_debug("Received response from Gemini API");
 
     let parsed_response: GeminiResponse = serde_json::from_str(&response_text).map_err(|e| {
         utils::log_error(&format!("JSON parsing error: {}", e));
@@ -116,7 +118,7 @@ pub async fn query_gemini_api(prompt: String) -> Result<String, AiError> {
     if let Some(candidates) = parsed_response.candidates {
         if let Some(first_candidate) = candidates.first() {
             if let Some(first_part) = first_candidate.content.parts.first() {
-                utils::log_debug(&format!("Successfully extracted AI response"));
+                utils::log_debug("Successfully extracted AI response");
                 return Ok(first_part.text.clone());
             }
         }
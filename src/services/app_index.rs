@@ -0,0 +1,533 @@
+// src/services/app_index.rs - Persistent desktop application index
+use crate::providers::{applications::DesktopApp, desktop_entry};
+use crate::utils;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+
+/// Bump whenever `DesktopApp` or the cache layout changes so old caches are rebuilt
+const INDEX_VERSION: u32 = 8;
+const INDEX_FILE_NAME: &str = "applications.json";
+
+/// Modification time of a file or directory, used to detect changes without reparsing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Fingerprint {
+    secs: u64,
+    nanos: u32,
+}
+
+impl Fingerprint {
+    /// Marks a directory as changed; no real modification time has this many nanoseconds
+    const STALE: Self = Self { secs: 0, nanos: u32::MAX };
+
+    pub(crate) fn of(path: &Path) -> Option<Self> {
+        let modified = fs::metadata(path).ok()?.modified().ok()?;
+        let since_epoch = modified.duration_since(UNIX_EPOCH).ok()?;
+        Some(Self {
+            secs: since_epoch.as_secs(),
+            nanos: since_epoch.subsec_nanos(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedEntry {
+    fingerprint: Fingerprint,
+    /// `None` for desktop files that don't describe a displayable app, so they
+    /// aren't reparsed on every refresh either
+    app: Option<DesktopApp>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexCache {
+    version: u32,
+    /// Locale variants names were resolved for; a different locale rebuilds the cache
+    #[serde(default)]
+    locales: Vec<String>,
+    dirs: BTreeMap<PathBuf, Fingerprint>,
+    files: BTreeMap<PathBuf, CachedEntry>,
+}
+
+/// What a refresh had to do, mostly useful for logging
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshStats {
+    pub parsed: usize,
+    pub reused: usize,
+    pub removed: usize,
+}
+
+struct IndexState {
+    cache: IndexCache,
+    apps: Option<Arc<Vec<DesktopApp>>>,
+}
+
+/// Index of desktop applications persisted in `paths.cache_dir`.
+///
+/// Search directories are scanned recursively. A directory whose modification time
+/// matches the cache isn't listed again, so a warm start only stats the directories
+/// and the cached files in them. Changed directories are re-listed. Either way, only
+/// files whose modification time changed are parsed again. While watching, inotify
+/// events mark individual paths stale so the next lookup refreshes just those.
+pub struct AppIndex {
+    cache_file: PathBuf,
+    search_dirs: Vec<PathBuf>,
+    state: Mutex<IndexState>,
+    stale_paths: Arc<Mutex<HashSet<PathBuf>>>,
+    watcher: Mutex<Option<RecommendedWatcher>>,
+}
+
+impl AppIndex {
+    pub fn new(cache_dir: &Path, search_dirs: Vec<PathBuf>) -> Self {
+        let cache_file = cache_dir.join(INDEX_FILE_NAME);
+        let cache = Self::load_cache(&cache_file);
+
+        Self {
+            cache_file,
+            search_dirs,
+            state: Mutex::new(IndexState { cache, apps: None }),
+            stale_paths: Arc::new(Mutex::new(HashSet::new())),
+            watcher: Mutex::new(None),
+        }
+    }
+
+    pub fn search_dirs(&self) -> &[PathBuf] {
+        &self.search_dirs
+    }
+
+    /// The current list of applications if it's up to date, without waiting for a
+    /// refresh that's in progress or doing one
+    pub fn cached_apps(&self) -> Option<Arc<Vec<DesktopApp>>> {
+        if self.stale_paths.lock().map_or(true, |paths| !paths.is_empty()) {
+            return None;
+        }
+        self.state.try_lock().ok()?.apps.clone()
+    }
+
+    /// Current list of applications, refreshing the index first if anything changed
+    pub fn apps(&self) -> Arc<Vec<DesktopApp>> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+
+        let stale: Vec<PathBuf> = self
+            .stale_paths
+            .lock()
+            .map(|mut paths| paths.drain().collect())
+            .unwrap_or_default();
+
+        if let (Some(apps), true) = (&state.apps, stale.is_empty()) {
+            return Arc::clone(apps);
+        }
+
+        // Directories are marked rather than removed so that the walk from the
+        // search directories still reaches them
+        for path in &stale {
+            state.cache.files.remove(path);
+            for dir in std::iter::once(path.as_path()).chain(path.parent()) {
+                if let Some(fingerprint) = state.cache.dirs.get_mut(dir) {
+                    *fingerprint = Fingerprint::STALE;
+                }
+            }
+        }
+
+        let stats = self.refresh(&mut state.cache);
+        let apps = Arc::new(self.collect_apps(&state.cache));
+        utils::log_info(&format!(
+            "Indexed {} desktop applications (parsed {}, reused {}, removed {})",
+            apps.len(),
+            stats.parsed,
+            stats.reused,
+            stats.removed
+        ));
+
+        if stats.parsed > 0 || stats.removed > 0 || state.apps.is_none() {
+            self.save_cache(&state.cache);
+        }
+
+        state.apps = Some(Arc::clone(&apps));
+        apps
+    }
+
+    /// Watch the search directories (and their subdirectories) with inotify so changes
+    /// are picked up without restarting. Only worth doing for long-running interactive
+    /// sessions.
+    pub fn watch(&self) {
+        let mut watcher_slot = self.watcher.lock().unwrap_or_else(|e| e.into_inner());
+        if watcher_slot.is_some() {
+            return;
+        }
+
+        let stale_paths = Arc::clone(&self.stale_paths);
+        let handler = move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else { return };
+            if matches!(event.kind, EventKind::Access(_) | EventKind::Other) {
+                return;
+            }
+            if let Ok(mut stale) = stale_paths.lock() {
+                stale.extend(event.paths);
+            }
+        };
+
+        let mut watcher = match notify::recommended_watcher(handler) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                utils::log_warn(&format!("Failed to start application directory watcher: {}", e));
+                return;
+            }
+        };
+
+        for dir in self.search_dirs.iter().filter(|dir| dir.is_dir()) {
+            if let Err(e) = watcher.watch(dir, RecursiveMode::Recursive) {
+                utils::log_warn(&format!("Failed to watch {}: {}", dir.display(), e));
+            }
+        }
+
+        utils::log_debug("Watching application directories for changes");
+        *watcher_slot = Some(watcher);
+    }
+
+    fn refresh(&self, cache: &mut IndexCache) -> RefreshStats {
+        let mut stats = RefreshStats::default();
+
+        for root in &self.search_dirs {
+            // Symlinked directories are followed, but each real directory is only
+            // walked once so that a link back up the tree doesn't loop forever
+            let mut visited = HashSet::new();
+            let mut pending = vec![root.clone()];
+            while let Some(dir) = pending.pop() {
+                if !visited.insert(fs::canonicalize(&dir).unwrap_or_else(|_| dir.clone())) {
+                    continue;
+                }
+                Self::refresh_dir(cache, root, &dir, &mut pending, &mut stats);
+            }
+        }
+
+        stats
+    }
+
+    /// Bring one directory below `root` up to date, queueing its subdirectories
+    fn refresh_dir(
+        cache: &mut IndexCache,
+        root: &Path,
+        dir: &Path,
+        pending: &mut Vec<PathBuf>,
+        stats: &mut RefreshStats,
+    ) {
+        let Some(dir_fingerprint) = Fingerprint::of(dir) else {
+            // Directory vanished; forget everything that lived in it
+            if cache.dirs.remove(dir).is_some() {
+                stats.removed += Self::forget_dir(cache, dir);
+            }
+            return;
+        };
+
+        if cache.dirs.get(dir) == Some(&dir_fingerprint) {
+            // Editing a file in place leaves its directory untouched, so the files
+            // still need checking, but nothing new needs listing
+            let cached_files: Vec<PathBuf> =
+                cache.files.keys().filter(|path| path.parent() == Some(dir)).cloned().collect();
+            for path in cached_files {
+                if !Self::refresh_file(cache, root, &path, stats) {
+                    cache.files.remove(&path);
+                    stats.removed += 1;
+                }
+            }
+            pending.extend(cache.dirs.keys().filter(|path| path.parent() == Some(dir)).cloned());
+            return;
+        }
+
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                utils::log_warn(&format!("Failed to read directory {}: {}", dir.display(), e));
+                return;
+            }
+        };
+
+        let mut present = HashSet::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                present.insert(path.clone());
+                pending.push(path);
+                continue;
+            }
+            if path.extension().and_then(|s| s.to_str()) != Some("desktop") {
+                continue;
+            }
+            if Self::refresh_file(cache, root, &path, stats) {
+                present.insert(path);
+            }
+        }
+
+        let before = cache.files.len();
+        cache.files.retain(|path, _| path.parent() != Some(dir) || present.contains(path));
+        stats.removed += before - cache.files.len();
+
+        let removed_dirs: Vec<PathBuf> = cache
+            .dirs
+            .keys()
+            .filter(|path| path.parent() == Some(dir) && !present.contains(*path))
+            .cloned()
+            .collect();
+        for removed_dir in removed_dirs {
+            stats.removed += Self::forget_dir(cache, &removed_dir);
+        }
+
+        cache.dirs.insert(dir.to_path_buf(), dir_fingerprint);
+    }
+
+    /// Parse a desktop file again if its modification time changed. Returns false
+    /// if it can no longer be indexed.
+    fn refresh_file(
+        cache: &mut IndexCache,
+        root: &Path,
+        path: &Path,
+        stats: &mut RefreshStats,
+    ) -> bool {
+        let (Some(fingerprint), Some(desktop_id)) =
+            (Fingerprint::of(path), desktop_entry::desktop_file_id(root, path))
+        else {
+            return false;
+        };
+
+        let unchanged = cache
+            .files
+            .get(path)
+            .is_some_and(|cached| cached.fingerprint == fingerprint);
+        if unchanged {
+            stats.reused += 1;
+            return true;
+        }
+
+        let app = DesktopApp::from_desktop_file(path, desktop_id);
+        cache.files.insert(path.to_path_buf(), CachedEntry { fingerprint, app });
+        stats.parsed += 1;
+        true
+    }
+
+    /// Drop a directory and everything below it, returning how many files went
+    fn forget_dir(cache: &mut IndexCache, dir: &Path) -> usize {
+        cache.dirs.retain(|path, _| !path.starts_with(dir));
+        let before = cache.files.len();
+        cache.files.retain(|path, _| !path.starts_with(dir));
+        before - cache.files.len()
+    }
+
+    /// Build the app list in search-directory order. Later directories override
+    /// earlier ones with the same desktop file ID, even when the overriding entry is
+    /// hidden. Entries that shouldn't be shown on this desktop are then dropped.
+    /// Apps that merely share a name are kept apart.
+    fn collect_apps(&self, cache: &IndexCache) -> Vec<DesktopApp> {
+        let mut by_id: Vec<&DesktopApp> = Vec::new();
+        let mut seen_ids = std::collections::HashMap::new();
+
+        for dir in &self.search_dirs {
+            let in_dir = cache
+                .files
+                .iter()
+                .filter(|(path, _)| path.starts_with(dir))
+                .filter_map(|(_, entry)| entry.app.as_ref());
+
+            for app in in_dir {
+                if let Some(existing_index) = seen_ids.get(&app.desktop_id) {
+                    by_id[*existing_index] = app;
+                } else {
+                    seen_ids.insert(app.desktop_id.clone(), by_id.len());
+                    by_id.push(app);
+                }
+            }
+        }
+
+        let desktops = desktop_entry::current_desktops();
+        by_id
+            .into_iter()
+            .filter(|app| app.is_visible(&desktops))
+            .cloned()
+            .collect()
+    }
+
+    fn load_cache(cache_file: &Path) -> IndexCache {
+        let cache = fs::read_to_string(cache_file)
+            .ok()
+            .and_then(|content| serde_json::from_str::<IndexCache>(&content).ok());
+
+        let locales = desktop_entry::current_locales();
+        match cache {
+            Some(cache) if cache.version == INDEX_VERSION && cache.locales == locales => cache,
+            _ => IndexCache {
+                version: INDEX_VERSION,
+                locales,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn save_cache(&self, cache: &IndexCache) {
+        if let Some(parent) = self.cache_file.parent() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                utils::log_warn(&format!("Failed to create cache directory: {}", e));
+                return;
+            }
+        }
+
+        match serde_json::to_string(cache) {
+            Ok(content) => {
+                if let Err(e) = fs::write(&self.cache_file, content) {
+                    utils::log_warn(&format!("Failed to write application index: {}", e));
+                }
+            }
+            Err(e) => utils::log_warn(&format!("Failed to serialize application index: {}", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_desktop_file(dir: &Path, file_name: &str, name: &str) {
+        let content = format!("[Desktop Entry]\nType=Application\nName={}\nExec={}\n", name, name.to_lowercase());
+        fs::write(dir.join(file_name), content).unwrap();
+    }
+
+    #[test]
+    fn test_warm_cache_reuses_entries_and_picks_up_new_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let apps_dir = root.join("applications");
+        let cache_dir = root.join("cache");
+        fs::create_dir_all(&apps_dir).unwrap();
+
+        write_desktop_file(&apps_dir, "firefox.desktop", "Firefox");
+        write_desktop_file(&apps_dir, "kitty.desktop", "Kitty");
+
+        let cold = AppIndex::new(&cache_dir, vec![apps_dir.clone()]);
+        assert_eq!(cold.apps().len(), 2);
+
+        // A fresh index loaded from the cache shouldn't parse anything
+        let warm = AppIndex::new(&cache_dir, vec![apps_dir.clone()]);
+        let mut state = warm.state.lock().unwrap();
+        let stats = warm.refresh(&mut state.cache);
+        assert_eq!(stats, RefreshStats { parsed: 0, reused: 2, removed: 0 });
+
+        // Rewriting a file in place doesn't change its directory's modification time
+        let dir_modified = fs::metadata(&apps_dir).unwrap().modified().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        write_desktop_file(&apps_dir, "firefox.desktop", "Firefox Nightly");
+        fs::File::open(&apps_dir).unwrap().set_modified(dir_modified).unwrap();
+        let stats = warm.refresh(&mut state.cache);
+        assert_eq!(stats, RefreshStats { parsed: 1, reused: 1, removed: 0 });
+
+        // Adding a file changes the directory fingerprint; only the new file is parsed
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        write_desktop_file(&apps_dir, "code.desktop", "Code");
+        fs::remove_file(apps_dir.join("kitty.desktop")).unwrap();
+        let stats = warm.refresh(&mut state.cache);
+        assert_eq!(stats, RefreshStats { parsed: 1, reused: 1, removed: 1 });
+
+        let names: Vec<_> = warm.collect_apps(&state.cache).into_iter().map(|a| a.name).collect();
+        assert_eq!(names, vec!["Code", "Firefox Nightly"]);
+    }
+
+    #[test]
+    fn test_symlink_cycles_are_walked_once() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let apps_dir = root.join("applications");
+        fs::create_dir_all(apps_dir.join("kde")).unwrap();
+
+        write_desktop_file(&apps_dir.join("kde"), "konsole.desktop", "Konsole");
+        std::os::unix::fs::symlink(&apps_dir, apps_dir.join("kde").join("up")).unwrap();
+
+        let index = AppIndex::new(&root.join("cache"), vec![apps_dir]);
+        let ids: Vec<_> = index.apps().iter().map(|a| a.desktop_id.clone()).collect();
+        assert_eq!(ids, vec!["kde-konsole.desktop"]);
+    }
+
+    #[test]
+    fn test_hidden_entries_mask_lower_precedence_directories() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let system_dir = root.join("system");
+        let user_dir = root.join("user");
+        fs::create_dir_all(&system_dir).unwrap();
+        fs::create_dir_all(&user_dir).unwrap();
+
+        write_desktop_file(&system_dir, "firefox.desktop", "Firefox");
+        write_desktop_file(&system_dir, "kitty.desktop", "Kitty");
+        fs::write(user_dir.join("firefox.desktop"), "[Desktop Entry]\nHidden=true\n").unwrap();
+        fs::write(
+            user_dir.join("ghost.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Ghost\nExec=ghost\nTryExec=wayfindr-not-installed\n",
+        )
+        .unwrap();
+
+        let index = AppIndex::new(&root.join("cache"), vec![system_dir, user_dir]);
+        let names: Vec<_> = index.apps().iter().map(|a| a.name.clone()).collect();
+        assert_eq!(names, vec!["Kitty"]);
+    }
+
+    #[test]
+    fn test_desktop_actions_are_indexed() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let apps_dir = root.join("applications");
+        fs::create_dir_all(&apps_dir).unwrap();
+
+        fs::write(
+            apps_dir.join("firefox.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Firefox\nExec=firefox %u\nActions=new-private-window;dbus-only;\n\n\
+             [Desktop Action new-private-window]\nName=New Private Window\nExec=firefox --private-window %u\n\n\
+             [Desktop Action dbus-only]\nName=Activated Over D-Bus\n",
+        )
+        .unwrap();
+
+        let index = AppIndex::new(&root.join("cache"), vec![apps_dir]);
+        let apps = index.apps();
+        let actions: Vec<_> = apps[0].actions.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(actions, vec!["New Private Window"]);
+
+        let results = apps[0].action_results("applications");
+        assert_eq!(results[0].title, "Firefox: New Private Window");
+    }
+
+    #[test]
+    fn test_subdirectories_get_prefixed_ids_and_same_names_stay_apart() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let system_dir = root.join("system");
+        let user_dir = root.join("user");
+        fs::create_dir_all(system_dir.join("kde")).unwrap();
+        fs::create_dir_all(&user_dir).unwrap();
+
+        write_desktop_file(&system_dir.join("kde"), "konsole.desktop", "Terminal");
+        write_desktop_file(&system_dir, "xterm.desktop", "Terminal");
+        // Overrides the nested entry by ID, not by its file name
+        fs::write(
+            user_dir.join("kde-konsole.desktop"),
+            "[Desktop Entry]\nType=Application\nName=Terminal\nExec=konsole --profile user\n",
+        )
+        .unwrap();
+
+        let index = AppIndex::new(&root.join("cache"), vec![system_dir.clone(), user_dir]);
+        let mut apps: Vec<_> = index.apps().iter().map(|a| (a.desktop_id.clone(), a.exec.clone())).collect();
+        apps.sort();
+        assert_eq!(
+            apps,
+            vec![
+                ("kde-konsole.desktop".to_string(), "konsole --profile user".to_string()),
+                ("xterm.desktop".to_string(), "terminal".to_string()),
+            ]
+        );
+
+        // Removing a subdirectory forgets its entries on the next refresh
+        let mut state = index.state.lock().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        fs::remove_dir_all(system_dir.join("kde")).unwrap();
+        let stats = index.refresh(&mut state.cache);
+        assert_eq!(stats.removed, 1);
+    }
+}
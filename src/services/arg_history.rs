@@ -0,0 +1,105 @@
+// src/services/arg_history.rs - Arguments previously passed to launched results
+use crate::config;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Argument strings kept per result; older ones are dropped first
+const MAX_ENTRIES_PER_RESULT: usize = 20;
+
+/// Argument strings typed for each result ID, most recent first, stored as JSON
+#[derive(Debug, Default)]
+pub struct ArgHistory {
+    entries: HashMap<String, Vec<String>>,
+    file_path: PathBuf,
+}
+
+impl ArgHistory {
+    /// Load the history next to the usage stats, starting empty if there is none
+    pub fn load() -> Self {
+        Self::load_from(&config::get_config().paths.config_dir.join("arg_history.json"))
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        let entries = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| match serde_json::from_str(&content) {
+                Ok(entries) => Some(entries),
+                Err(e) => {
+                    crate::utils::log_warn(&format!("Ignoring unreadable argument history {}: {}", path.display(), e));
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        Self {
+            entries,
+            file_path: path.to_path_buf(),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.file_path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create argument history directory")?;
+        }
+        let content = serde_json::to_string_pretty(&self.entries).context("Failed to serialize argument history")?;
+        std::fs::write(&self.file_path, content).context("Failed to write argument history")
+    }
+
+    /// Remember `args` for `result_id`, moving it to the front if it was used before
+    pub fn record(&mut self, result_id: &str, args: &str) {
+        let args = args.trim();
+        if args.is_empty() {
+            return;
+        }
+
+        let history = self.entries.entry(result_id.to_string()).or_default();
+        history.retain(|previous| previous != args);
+        history.insert(0, args.to_string());
+        history.truncate(MAX_ENTRIES_PER_RESULT);
+    }
+
+    /// Earlier argument strings for `result_id` that start with `prefix`, most
+    /// recent first
+    pub fn completions(&self, result_id: &str, prefix: &str) -> Vec<String> {
+        self.entries
+            .get(result_id)
+            .map(|history| {
+                history
+                    .iter()
+                    .filter(|args| args.starts_with(prefix) && args.as_str() != prefix)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_is_per_result_and_most_recent_first() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("arg_history.json");
+
+        let mut history = ArgHistory::load_from(&path);
+        history.record("app:firefox.desktop", "--private-window");
+        history.record("app:firefox.desktop", "https://example.com");
+        history.record("app:firefox.desktop", " --private-window ");
+        history.record("app:code.desktop", "--new-window ~/src");
+        history.record("app:code.desktop", "   ");
+        history.save().unwrap();
+
+        let history = ArgHistory::load_from(&path);
+        assert_eq!(
+            history.completions("app:firefox.desktop", ""),
+            vec!["--private-window", "https://example.com"]
+        );
+        assert_eq!(history.completions("app:firefox.desktop", "https"), vec!["https://example.com"]);
+        assert!(history.completions("app:firefox.desktop", "https://example.com").is_empty());
+        assert_eq!(history.completions("app:code.desktop", ""), vec!["--new-window ~/src"]);
+        assert!(history.completions("app:kitty.desktop", "").is_empty());
+    }
+}
@@ -1,14 +1,46 @@
 // src/services/directory_autocomplete.rs - Directory path completion
+use crate::config::DirectoriesProviderConfig;
+use crate::providers::files;
+use crate::services::frecency;
 use crate::types::ActionResult;
 use crate::utils;
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
 use std::fs;
 
-pub struct DirectoryAutocomplete;
+const MAX_RESULTS: usize = 20;
+/// Directories an abbreviated path may expand to before the last segment is listed
+const MAX_EXPANSIONS: usize = 32;
+
+/// A directory the typed path may refer to, with the path as the user would write it
+#[derive(Debug, Clone)]
+struct Candidate {
+    path: PathBuf,
+    display: String,
+    /// Sum of the match quality of every expanded segment; lower is better
+    penalty: u32,
+}
+
+pub struct DirectoryAutocomplete {
+    /// Offer files as well as directories
+    include_files: bool,
+    /// Where `./` and `../` paths start from
+    relative_to: PathBuf,
+}
 
 impl DirectoryAutocomplete {
     pub fn new() -> Self {
-        Self
+        Self {
+            include_files: false,
+            relative_to: dirs::home_dir().unwrap_or_else(|| PathBuf::from("/")),
+        }
+    }
+
+    pub fn from_config(settings: &DirectoriesProviderConfig) -> Self {
+        Self {
+            include_files: settings.complete_files,
+            relative_to: PathBuf::from(shellexpand::tilde(&settings.relative_to).as_ref()),
+        }
     }
 
     /// Get directory completions for a partial path
@@ -19,120 +51,120 @@ impl DirectoryAutocomplete {
 
         // Check if this looks like a path
         if self.looks_like_path(input) {
-            self.complete_path(input)
+            self.complete_path(input, &frecency::scores())
         } else {
             Vec::new()
         }
     }
 
     fn looks_like_path(&self, input: &str) -> bool {
-        input.starts_with('/') || 
-        input.starts_with("~/") || 
-        input.starts_with("./") || 
+        input.starts_with('/') ||
+        input.starts_with("~/") ||
+        input.starts_with("./") ||
         input.starts_with("../") ||
         input.contains('/')
     }
 
-    fn complete_path(&self, input: &str) -> Vec<ActionResult> {
-        let expanded = shellexpand::tilde(input).into_owned();
-        let path = Path::new(&expanded);
-        
-        if expanded.ends_with('/') {
-            // User typed "/path/to/dir/" - show contents of that dir
-            self.scan_directory(path, "", input.starts_with("~/"))
-        } else {
-            // User typed "/path/to/par" - show completions starting with "par"
-            match path.parent() {
-                Some(parent) => {
-                    // Create a longer lived value for the filename
-                    let filename = path.file_name()
-                        .unwrap_or_default()
-                        .to_string_lossy()
-                        .to_string();
-                    self.scan_directory(parent, &filename, input.starts_with("~/"))
-                },
-                None => self.scan_directory(path, "", input.starts_with("~/")),
-            }
+    /// Expand every segment but the last fish-style, so `~/d/wf/sr` reaches
+    /// `~/Dev/wayfindr/src`, then list what the last segment matches in each of the
+    /// directories reached. Better matches come first, then more frecent paths.
+    fn complete_path(&self, input: &str, scores: &HashMap<String, f64>) -> Vec<ActionResult> {
+        let (root, rest) = self.root(input);
+        let mut segments: Vec<&str> = rest.split('/').collect();
+        let mut partial = segments.pop().unwrap_or_default();
+        if partial == ".." {
+            // "~/Dev/.." lists ~ like "~/Dev/../" would
+            segments.push(partial);
+            partial = "";
+        }
+
+        let mut candidates = vec![root];
+        for segment in segments {
+            candidates = candidates
+                .iter()
+                .flat_map(|candidate| expand_segment(candidate, segment))
+                .collect();
+            candidates.sort_by_key(|candidate| candidate.penalty);
+            candidates.truncate(MAX_EXPANSIONS);
         }
+
+        let mut matches: Vec<(Candidate, bool)> = candidates
+            .iter()
+            .flat_map(|candidate| self.scan_directory(candidate, partial))
+            .collect();
+
+        let score = |candidate: &Candidate| scores.get(candidate.path.to_string_lossy().as_ref()).copied().unwrap_or(0.0);
+        matches.sort_by(|(a, a_is_dir), (b, b_is_dir)| {
+            a.penalty
+                .cmp(&b.penalty)
+                .then_with(|| score(b).total_cmp(&score(a)))
+                .then_with(|| b_is_dir.cmp(a_is_dir))
+                .then_with(|| a.display.cmp(&b.display))
+        });
+        matches.truncate(MAX_RESULTS);
+
+        matches
+            .into_iter()
+            .map(|(candidate, is_dir)| completion_result(&candidate, is_dir))
+            .collect()
     }
 
-    fn scan_directory(&self, dir: &Path, partial: &str, tilde_prefix: bool) -> Vec<ActionResult> {
-        let mut results = Vec::new();
+    /// The directory the typed path starts from, and the rest of the path
+    fn root<'a>(&self, input: &'a str) -> (Candidate, &'a str) {
+        let candidate = |path: PathBuf, display: &str| Candidate {
+            path,
+            display: display.to_string(),
+            penalty: 0,
+        };
 
-        if !dir.exists() || !dir.is_dir() {
-            return results;
+        if let Some(rest) = input.strip_prefix('/') {
+            (candidate(PathBuf::from("/"), ""), rest)
+        } else if let Some(rest) = input.strip_prefix("~/") {
+            let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("/"));
+            (candidate(home, "~"), rest)
+        } else {
+            let rest = input.strip_prefix("./").unwrap_or(input);
+            (candidate(self.relative_to.clone(), "."), rest)
         }
+    }
 
-        match fs::read_dir(dir) {
-            Ok(entries) => {
-                for entry in entries.flatten() {
-                    let entry_path = entry.path();
-                    
-                    // Only include directories
-                    if !entry_path.is_dir() {
-                        continue;
-                    }
-
-                    let name = entry_path.file_name()
-                        .unwrap_or_default()
-                        .to_string_lossy();
-
-                    // Skip hidden directories unless user is explicitly typing them
-                    if name.starts_with('.') && !partial.starts_with('.') {
-                        continue;
-                    }
-
-                    // Filter by partial match
-                    if !partial.is_empty() && !name.to_lowercase().starts_with(&partial.to_lowercase()) {
-                        continue;
-                    }
-
-                    // Create the full path for display
-                    let display_path = if tilde_prefix {
-                        // Convert back to ~ notation if originally had it
-                        if let Some(home) = std::env::var("HOME").ok() {
-                            let full_path = entry_path.to_string_lossy();
-                            if full_path.starts_with(&home) {
-                                full_path.replacen(&home, "~", 1)
-                            } else {
-                                full_path.to_string()
-                            }
-                        } else {
-                            entry_path.to_string_lossy().to_string()
-                        }
-                    } else {
-                        entry_path.to_string_lossy().to_string()
-                    };
-
-                    let result = ActionResult::new_navigate(
-                        utils::generate_id("autocomplete", &display_path),
-                        "directories",
-                        name.to_string(),
-                        entry_path.to_string_lossy().to_string(),
-                    ).with_description(format!("Navigate to {}", display_path));
-
-                    results.push(result);
-                }
-            }
+    /// Entries of `dir` matching `partial`, and whether each is a directory
+    fn scan_directory(&self, dir: &Candidate, partial: &str) -> Vec<(Candidate, bool)> {
+        let entries = match fs::read_dir(&dir.path) {
+            Ok(entries) => entries,
             Err(e) => {
-                utils::log_debug(&format!("Failed to read directory {}: {}", dir.display(), e));
+                utils::log_debug(&format!("Failed to read directory {}: {}", dir.path.display(), e));
+                return Vec::new();
             }
-        }
+        };
 
-        // Sort by name
-        results.sort_by(|a, b| a.title.cmp(&b.title));
-        
-        // Limit results
-        results.truncate(20);
+        entries
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
 
-        results
+                // Skip hidden entries unless the user is explicitly typing them
+                if name.starts_with('.') && !partial.starts_with('.') {
+                    return None;
+                }
+
+                // Follows symlinks, so a link to a directory completes like one
+                let is_dir = entry.path().is_dir();
+                if !is_dir && !self.include_files {
+                    return None;
+                }
+
+                let quality = if partial.is_empty() { 0 } else { match_quality(&name, partial)? };
+                Some((dir.child(&name, quality), is_dir))
+            })
+            .collect()
     }
 
     fn get_common_directories(&self) -> Vec<ActionResult> {
         let common_dirs = vec![
             ("~", "Home"),
             ("~/Documents", "Documents"),
-            ("~/Downloads", "Downloads"), 
+            ("~/Downloads", "Downloads"),
             ("~/Desktop", "Desktop"),
             ("~/Pictures", "Pictures"),
             ("~/Videos", "Videos"),
@@ -164,8 +196,173 @@ impl DirectoryAutocomplete {
     }
 }
 
+impl Candidate {
+    fn child(&self, name: &str, quality: u32) -> Self {
+        let display = match self.display.as_str() {
+            "." => name.to_string(),
+            display => format!("{}/{}", display, name),
+        };
+        Self {
+            path: self.path.join(name),
+            display,
+            penalty: self.penalty + quality,
+        }
+    }
+
+    /// Go up a level, dropping the last segment typed rather than resolving
+    /// symlinks, the way `cd ..` does
+    fn parent(&self) -> Self {
+        let mut path = self.path.clone();
+        match path.components().next_back() {
+            Some(Component::Normal(_)) => {
+                path.pop();
+            }
+            Some(Component::RootDir) => {}
+            _ => path.push(".."),
+        }
+
+        let last = self.display.rsplit('/').next().unwrap_or_default();
+        let display = match (self.display.rsplit_once('/'), last) {
+            _ if self.display.is_empty() => String::new(),
+            (_, ".") => "..".to_string(),
+            (_, ".." | "~") => format!("{}/..", self.display),
+            (Some((parent, _)), _) => parent.to_string(),
+            (None, _) => ".".to_string(),
+        };
+        Self {
+            path,
+            display,
+            penalty: self.penalty,
+        }
+    }
+}
+
+/// Directories below `dir` that an intermediate `segment` of the typed path may
+/// stand for. An existing directory of that exact name is taken on its own.
+fn expand_segment(dir: &Candidate, segment: &str) -> Vec<Candidate> {
+    match segment {
+        "" | "." => return vec![dir.clone()],
+        ".." => return vec![dir.parent()],
+        _ => {}
+    }
+
+    if dir.path.join(segment).is_dir() {
+        return vec![dir.child(segment, 0)];
+    }
+
+    let Ok(entries) = fs::read_dir(&dir.path) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') && !segment.starts_with('.') {
+                return None;
+            }
+            let quality = match_quality(&name, segment)?;
+            Some(dir.child(&name, quality))
+        })
+        .collect()
+}
+
+/// How well `name` matches a typed `segment`: 0 for the same name in another
+/// case, then prefix, substring and in-order subsequence matches
+fn match_quality(name: &str, segment: &str) -> Option<u32> {
+    let name = name.to_lowercase();
+    let segment = segment.to_lowercase();
+
+    if name == segment {
+        Some(0)
+    } else if name.starts_with(&segment) {
+        Some(1)
+    } else if name.contains(&segment) {
+        Some(2)
+    } else if utils::fuzzy_match(&name, &segment) {
+        Some(3)
+    } else {
+        None
+    }
+}
+
+fn completion_result(candidate: &Candidate, is_dir: bool) -> ActionResult {
+    let path = candidate.path.to_string_lossy().to_string();
+    let name = candidate.path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let link_target = fs::read_link(&candidate.path).ok();
+
+    let mut result = if is_dir {
+        let mut result = ActionResult::new_navigate(
+            utils::generate_id("autocomplete", &candidate.display),
+            "directories",
+            name,
+            path,
+        )
+        .with_description(format!("Navigate to {}", candidate.display));
+        result.metadata.icon = Some("folder".to_string());
+        result
+    } else {
+        files::file_result(&candidate.path).with_description(candidate.display.clone())
+    };
+
+    if let Some(target) = link_target {
+        result.title.push_str(" ⇢");
+        result.description.push_str(&format!(" (symlink to {})", target.display()));
+        result.metadata.tags.push("symlink".to_string());
+    }
+    result
+}
+
 impl Default for DirectoryAutocomplete {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_segments_expand_fuzzily_and_rank_by_frecency() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("Dev/wayfindr/src")).unwrap();
+        fs::create_dir_all(root.join("Dev/wayfindr/scripts")).unwrap();
+        fs::create_dir_all(root.join("Documents/work-files/src-old")).unwrap();
+        fs::write(root.join("Dev/wayfindr/src.txt"), "").unwrap();
+        std::os::unix::fs::symlink(root.join("Dev/wayfindr"), root.join("wf-link")).unwrap();
+
+        let mut autocomplete = DirectoryAutocomplete::new();
+        autocomplete.relative_to = root.to_path_buf();
+        let titles = |results: Vec<ActionResult>| results.iter().map(|r| r.description.clone()).collect::<Vec<_>>();
+
+        // "d" expands to Dev and Documents and "wf" to wayfindr and work-files;
+        // "scripts" only matches "sr" as a subsequence, so it ranks last
+        let results = autocomplete.complete_path("./d/wf/sr", &HashMap::new());
+        assert_eq!(
+            titles(results),
+            vec![
+                "Navigate to Dev/wayfindr/src",
+                "Navigate to Documents/work-files/src-old",
+                "Navigate to Dev/wayfindr/scripts",
+            ]
+        );
+
+        // Files only when asked for; frecency beats alphabetical order
+        autocomplete.include_files = true;
+        let scores = HashMap::from([(root.join("Dev/wayfindr/src").to_string_lossy().to_string(), 4.0)]);
+        let results = autocomplete.complete_path("Dev/wayfindr/s", &scores);
+        assert_eq!(
+            titles(results),
+            vec!["Navigate to Dev/wayfindr/src", "Navigate to Dev/wayfindr/scripts", "Dev/wayfindr/src.txt"]
+        );
+
+        // ".." goes up a level lexically, and symlinks are marked
+        let results = autocomplete.complete_path("Dev/wayfindr/../..", &HashMap::new());
+        let link = results.iter().find(|r| r.title.starts_with("wf-link")).unwrap();
+        assert_eq!(link.title, "wf-link ⇢");
+        assert!(link.description.ends_with(&format!("(symlink to {})", root.join("Dev/wayfindr").display())));
+        assert!(matches!(&link.action, crate::types::ActionType::Navigate { path, .. } if path == &root.join("wf-link").to_string_lossy()));
+    }
+}
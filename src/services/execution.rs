@@ -1,17 +1,31 @@
 // src/services/execution.rs
 use crate::{
     config::get_config,
-    services::usage,
+    providers::{
+        windows::{HyprctlBackend, WindowBackend},
+        ProviderManager,
+    },
+    services::{frecency, navigate, usage},
     types::{ActionData, ActionResult, ActionType, AppResult},
     utils,
 };
 use std::process::Command;
+use std::sync::Arc;
 
-pub struct ExecutionService;
+pub struct ExecutionService {
+    /// Picks the app that open results run, from the indexes the searches use
+    providers: Arc<ProviderManager>,
+    /// Focuses the windows behind focus results
+    windows: Arc<dyn WindowBackend>,
+}
 
 impl ExecutionService {
-    pub fn new() -> Self {
-        Self
+    pub fn new(providers: Arc<ProviderManager>) -> Self {
+        Self::with_window_backend(providers, Arc::new(HyprctlBackend))
+    }
+
+    pub fn with_window_backend(providers: Arc<ProviderManager>, windows: Arc<dyn WindowBackend>) -> Self {
+        Self { providers, windows }
     }
 
     /// Execute an action and return whether the app should exit
@@ -28,7 +42,9 @@ impl ExecutionService {
             ActionType::Launch { needs_terminal } => {
                 self.execute_launch(action, *needs_terminal).await
             }
-            ActionType::Navigate { path } => self.execute_navigate(path).await,
+            ActionType::Navigate { path, handler } => self.execute_navigate(path, handler.as_deref()).await,
+            ActionType::Open { path } => self.execute_open(path).await,
+            ActionType::Focus { address } => self.execute_focus(address).await,
             ActionType::AiResponse => {
                 // AI responses don't need execution, just display
                 Ok(false)
@@ -38,8 +54,25 @@ impl ExecutionService {
     }
 
     async fn execute_launch(&self, action: &ActionResult, needs_terminal: bool) -> AppResult<bool> {
+        let config = get_config();
+        let in_terminal = |command: String| {
+            if needs_terminal {
+                format!("{} -e {}", config.general.default_terminal, command)
+            } else {
+                command
+            }
+        };
+
         let command = match &action.data {
-            ActionData::Command(cmd) => cmd,
+            ActionData::Command(cmd) => in_terminal(cmd.clone()),
+            ActionData::Argv { argv, working_dir } => {
+                // hyprctl hands the command to a shell, so quote each argument
+                let command = in_terminal(utils::shell_join(argv));
+                match working_dir {
+                    Some(dir) => format!("cd {} && {}", utils::shell_quote(dir), command),
+                    None => command,
+                }
+            }
             _ => {
                 return Err(crate::types::AppError::ActionExecution(
                     "Launch action requires command data".to_string(),
@@ -47,34 +80,58 @@ impl ExecutionService {
             }
         };
 
-        let config = get_config();
-
-        if needs_terminal {
-            // Launch in terminal
-            let full_command = format!("{} -e {}", config.general.default_terminal, command);
-            self.execute_system_command(&full_command).await?;
-        } else {
-            // Launch directly
-            self.execute_system_command(command).await?;
-        }
+        self.execute_system_command(&command).await?;
 
         utils::log_info(&format!("Successfully launched application: {}", action.title));
         Ok(true) // Exit wayfindr after successfully launching applications
     }
 
-    async fn execute_navigate(&self, path: &str) -> AppResult<bool> {
+    async fn execute_navigate(&self, path: &str, handler: Option<&str>) -> AppResult<bool> {
         let config = get_config();
+        frecency::record_visit(path);
+
+        let handlers = navigate::handlers(config);
+        let name = handler.unwrap_or(&config.navigate.default_handler);
+        let handler = handlers.get(name).unwrap_or_else(|| {
+            utils::log_warn(&format!("Unknown navigate handler '{}', using {}", name, navigate::FALLBACK_HANDLER));
+            &handlers[navigate::FALLBACK_HANDLER]
+        });
+        let render = |template: &str| navigate::render(template, path, &config.general.default_terminal);
+
+        if let Some(print) = &handler.print {
+            navigate::set_pending_output(render(print));
+        } else if let Some(command) = &handler.command {
+            self.execute_system_command(&render(command)).await?;
+        } else {
+            return Err(crate::types::AppError::ActionExecution(format!(
+                "Navigate handler '{}' has neither a command nor print",
+                name
+            )));
+        }
 
-        // Navigate to directory using terminal
-        let shell_safe_path = format!("'{}'", path.replace("'", r"'\''"));
-        let command = format!(
-            "{} -d {} $SHELL",
-            config.general.default_terminal, shell_safe_path
-        );
+        Ok(true) // Exit after navigation
+    }
 
-        self.execute_system_command(&command).await?;
+    async fn execute_open(&self, path: &str) -> AppResult<bool> {
+        if let Some(result) = self.providers.default_open(path).await {
+            if let ActionType::Launch { needs_terminal } = result.action {
+                utils::log_debug(&format!("Opening {} with {}", path, result.title));
+                return self.execute_launch(&result, needs_terminal).await;
+            }
+        }
 
-        Ok(true) // Exit after navigation
+        utils::log_debug(&format!("No default app found for {}, using xdg-open", path));
+        self.execute_system_command(&utils::shell_join(&["xdg-open", path])).await?;
+        Ok(true) // Exit once the file is open
+    }
+
+    async fn execute_focus(&self, address: &str) -> AppResult<bool> {
+        self.windows
+            .focus(address)
+            .await
+            .map_err(|e| crate::types::AppError::ActionExecution(e.to_string()))?;
+
+        Ok(true) // Exit once the window has focus
     }
 
     async fn execute_custom(&self, action_id: &str, _action: &ActionResult) -> AppResult<bool> {
@@ -87,7 +144,7 @@ impl ExecutionService {
 
         // Use hyprctl dispatch exec for Hyprland integration
         let output = Command::new("hyprctl")
-            .args(&["dispatch", "exec", command])
+            .args(["dispatch", "exec", command])
             .output()
             .map_err(|e| {
                 crate::types::AppError::ActionExecution(format!("Failed to execute command: {}", e))
@@ -105,9 +162,3 @@ impl ExecutionService {
         Ok(())
     }
 }
-
-impl Default for ExecutionService {
-    fn default() -> Self {
-        Self::new()
-    }
-}
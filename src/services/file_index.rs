@@ -0,0 +1,412 @@
+// src/services/file_index.rs - Persistent index of files below configured roots
+use crate::services::app_index::Fingerprint;
+use crate::utils;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Bump whenever the cache layout changes so old caches are rebuilt
+const INDEX_VERSION: u32 = 1;
+const INDEX_FILE_NAME: &str = "files.json";
+/// Per-directory ignore files, in increasing precedence
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+/// What to index; a cache built with different settings is thrown away
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileIndexSettings {
+    pub roots: Vec<PathBuf>,
+    /// Directory levels below each root that are listed; 0 lists only the root
+    pub max_depth: usize,
+    pub include_hidden: bool,
+    /// Skip what `.gitignore`, `.ignore` and the global git excludes file ignore
+    pub respect_ignore_files: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DirRecord {
+    fingerprint: Fingerprint,
+    /// Ignore files in the directory; editing one doesn't change the directory's
+    /// own modification time
+    ignore_files: Vec<(String, Fingerprint)>,
+    /// Names of the files and subdirectories that weren't ignored
+    files: Vec<String>,
+    subdirs: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexCache {
+    version: u32,
+    settings: Option<FileIndexSettings>,
+    dirs: BTreeMap<PathBuf, DirRecord>,
+}
+
+/// What a refresh had to do, mostly useful for logging
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshStats {
+    pub listed: usize,
+    pub reused: usize,
+    pub removed: usize,
+}
+
+struct IndexState {
+    /// Taken out while a refresh runs
+    cache: Option<IndexCache>,
+    files: Arc<Vec<PathBuf>>,
+    refreshed_at: Option<Instant>,
+}
+
+/// Index of files below the configured roots, persisted in `paths.cache_dir`.
+///
+/// Works like the application index: a directory whose modification time and
+/// ignore files match the cache is trusted without listing it again. Refreshes run
+/// on a background thread, and searches use the last complete index meanwhile.
+pub struct FileIndex {
+    cache_file: PathBuf,
+    settings: FileIndexSettings,
+    refresh_interval: Duration,
+    state: Arc<Mutex<IndexState>>,
+}
+
+impl FileIndex {
+    pub fn new(cache_dir: &Path, settings: FileIndexSettings, refresh_interval: Duration) -> Self {
+        let cache_file = cache_dir.join(INDEX_FILE_NAME);
+        let cache = Self::load_cache(&cache_file, &settings);
+        let files = Arc::new(collect_files(&cache, &settings));
+
+        Self {
+            cache_file,
+            settings,
+            refresh_interval,
+            state: Arc::new(Mutex::new(IndexState {
+                cache: Some(cache),
+                files,
+                refreshed_at: None,
+            })),
+        }
+    }
+
+    pub fn settings(&self) -> &FileIndexSettings {
+        &self.settings
+    }
+
+    /// Indexed files as of the last refresh, starting one in the background if the
+    /// index is older than the refresh interval
+    pub fn files(&self) -> Arc<Vec<PathBuf>> {
+        self.refresh_in_background();
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        Arc::clone(&state.files)
+    }
+
+    /// Start a refresh on another thread unless one is running or the index is fresh
+    pub fn refresh_in_background(&self) {
+        let Some(cache) = self.take_cache_if_due() else {
+            return;
+        };
+
+        let state = Arc::clone(&self.state);
+        let settings = self.settings.clone();
+        let cache_file = self.cache_file.clone();
+        std::thread::spawn(move || Self::run_refresh(cache, &settings, &cache_file, &state));
+    }
+
+    /// Refresh on the calling thread
+    #[cfg(test)]
+    pub fn refresh_now(&self) -> RefreshStats {
+        match self.take_cache_if_due() {
+            Some(cache) => Self::run_refresh(cache, &self.settings, &self.cache_file, &self.state),
+            None => RefreshStats::default(),
+        }
+    }
+
+    fn take_cache_if_due(&self) -> Option<IndexCache> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let due = state
+            .refreshed_at
+            .is_none_or(|refreshed_at| refreshed_at.elapsed() >= self.refresh_interval);
+        if due { state.cache.take() } else { None }
+    }
+
+    fn run_refresh(
+        mut cache: IndexCache,
+        settings: &FileIndexSettings,
+        cache_file: &Path,
+        state: &Mutex<IndexState>,
+    ) -> RefreshStats {
+        let started = Instant::now();
+        let stats = refresh(&mut cache, settings);
+        let files = Arc::new(collect_files(&cache, settings));
+        utils::log_info(&format!(
+            "Indexed {} files in {:?} (listed {} directories, reused {}, removed {})",
+            files.len(),
+            started.elapsed(),
+            stats.listed,
+            stats.reused,
+            stats.removed
+        ));
+
+        if stats.listed > 0 || stats.removed > 0 {
+            save_cache(cache_file, &cache);
+        }
+
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+        state.cache = Some(cache);
+        state.files = files;
+        state.refreshed_at = Some(Instant::now());
+        stats
+    }
+
+    fn load_cache(cache_file: &Path, settings: &FileIndexSettings) -> IndexCache {
+        let cache = fs::read_to_string(cache_file)
+            .ok()
+            .and_then(|content| serde_json::from_str::<IndexCache>(&content).ok());
+
+        match cache {
+            Some(cache) if cache.version == INDEX_VERSION && cache.settings.as_ref() == Some(settings) => cache,
+            _ => IndexCache {
+                version: INDEX_VERSION,
+                settings: Some(settings.clone()),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+fn refresh(cache: &mut IndexCache, settings: &FileIndexSettings) -> RefreshStats {
+    let mut stats = RefreshStats::default();
+    let mut visited = HashSet::new();
+
+    let global = settings
+        .respect_ignore_files
+        .then(|| Gitignore::global().0)
+        .filter(|global| !global.is_empty());
+
+    for root in &settings.roots {
+        let rules: Vec<Arc<Gitignore>> = global.iter().cloned().map(Arc::new).collect();
+        refresh_dir(cache, settings, root, 0, &rules, false, &mut visited, &mut stats);
+    }
+
+    // Whatever wasn't reached is gone, ignored now or beyond the depth limit
+    let before = cache.dirs.len();
+    cache.dirs.retain(|dir, _| visited.contains(dir));
+    stats.removed += before - cache.dirs.len();
+
+    stats
+}
+
+/// Bring one directory up to date and recurse into its subdirectories. `rules` are
+/// the ignore matchers of its ancestors, innermost last; `rules_changed` forces a
+/// fresh listing when one of them changed since the cache was written.
+#[allow(clippy::too_many_arguments)]
+fn refresh_dir(
+    cache: &mut IndexCache,
+    settings: &FileIndexSettings,
+    dir: &Path,
+    depth: usize,
+    rules: &[Arc<Gitignore>],
+    rules_changed: bool,
+    visited: &mut HashSet<PathBuf>,
+    stats: &mut RefreshStats,
+) {
+    let Some(fingerprint) = Fingerprint::of(dir) else {
+        return;
+    };
+    visited.insert(dir.to_path_buf());
+
+    let ignore_files: Vec<(String, Fingerprint)> = if settings.respect_ignore_files {
+        IGNORE_FILES
+            .iter()
+            .filter_map(|name| Some((name.to_string(), Fingerprint::of(&dir.join(name))?)))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let cached = cache.dirs.get(dir);
+    let rules_changed = rules_changed || cached.is_none_or(|record| record.ignore_files != ignore_files);
+
+    let mut rules = rules.to_vec();
+    if !ignore_files.is_empty() {
+        let mut builder = GitignoreBuilder::new(dir);
+        for (name, _) in &ignore_files {
+            if let Some(e) = builder.add(dir.join(name)) {
+                utils::log_debug(&format!("Problem in {}: {}", dir.join(name).display(), e));
+            }
+        }
+        match builder.build() {
+            Ok(matcher) => rules.push(Arc::new(matcher)),
+            Err(e) => utils::log_warn(&format!("Ignoring rules in {}: {}", dir.display(), e)),
+        }
+    }
+
+    let subdirs = match cached {
+        Some(record) if record.fingerprint == fingerprint && !rules_changed => {
+            stats.reused += 1;
+            record.subdirs.clone()
+        }
+        _ => {
+            let Some(record) = list_dir(dir, fingerprint, ignore_files, &rules, settings) else {
+                return;
+            };
+            stats.listed += 1;
+            let subdirs = record.subdirs.clone();
+            cache.dirs.insert(dir.to_path_buf(), record);
+            subdirs
+        }
+    };
+
+    if depth < settings.max_depth {
+        for subdir in subdirs {
+            refresh_dir(cache, settings, &dir.join(subdir), depth + 1, &rules, rules_changed, visited, stats);
+        }
+    }
+}
+
+fn list_dir(
+    dir: &Path,
+    fingerprint: Fingerprint,
+    ignore_files: Vec<(String, Fingerprint)>,
+    rules: &[Arc<Gitignore>],
+    settings: &FileIndexSettings,
+) -> Option<DirRecord> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            utils::log_debug(&format!("Failed to read directory {}: {}", dir.display(), e));
+            return None;
+        }
+    };
+
+    let mut record = DirRecord {
+        fingerprint,
+        ignore_files,
+        files: Vec::new(),
+        subdirs: Vec::new(),
+    };
+
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name == ".git" || (!settings.include_hidden && name.starts_with('.')) {
+            continue;
+        }
+
+        // Symlinked directories are listed as files so the walk can't loop
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let is_dir = file_type.is_dir();
+        if is_ignored(rules, &entry.path(), is_dir) {
+            continue;
+        }
+
+        if is_dir {
+            record.subdirs.push(name);
+        } else {
+            record.files.push(name);
+        }
+    }
+
+    record.files.sort();
+    record.subdirs.sort();
+    Some(record)
+}
+
+/// The innermost matcher with an opinion decides, so a nested `!pattern` can bring
+/// back what a parent ignored
+fn is_ignored(rules: &[Arc<Gitignore>], path: &Path, is_dir: bool) -> bool {
+    rules
+        .iter()
+        .rev()
+        .map(|rules| rules.matched(path, is_dir))
+        .find(|matched| !matched.is_none())
+        .is_some_and(|matched| matches!(matched, Match::Ignore(_)))
+}
+
+/// Files of every indexed directory, in root order
+fn collect_files(cache: &IndexCache, settings: &FileIndexSettings) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    settings
+        .roots
+        .iter()
+        .flat_map(|root| cache.dirs.range(root.clone()..).take_while(move |(dir, _)| dir.starts_with(root)))
+        .filter(|(dir, _)| seen.insert(dir.as_path()))
+        .flat_map(|(dir, record)| record.files.iter().map(move |name| dir.join(name)))
+        .collect()
+}
+
+fn save_cache(cache_file: &Path, cache: &IndexCache) {
+    if let Some(parent) = cache_file.parent() {
+        if let Err(e) = fs::create_dir_all(parent) {
+            utils::log_warn(&format!("Failed to create cache directory: {}", e));
+            return;
+        }
+    }
+
+    match serde_json::to_string(cache) {
+        Ok(content) => {
+            if let Err(e) = fs::write(cache_file, content) {
+                utils::log_warn(&format!("Failed to write file index: {}", e));
+            }
+        }
+        Err(e) => utils::log_warn(&format!("Failed to serialize file index: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_respects_ignore_rules_depth_and_reuses_directories() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let home = root.join("home");
+        let cache_dir = root.join("cache");
+        fs::create_dir_all(home.join("project/target/debug")).unwrap();
+        fs::create_dir_all(home.join("project/src/deep/deeper")).unwrap();
+        fs::create_dir_all(home.join(".secrets")).unwrap();
+
+        fs::write(home.join("notes.md"), "").unwrap();
+        fs::write(home.join(".secrets/key.txt"), "").unwrap();
+        fs::write(home.join("project/.gitignore"), "target/\n*.log\n").unwrap();
+        fs::write(home.join("project/src/.ignore"), "!keep.log\n").unwrap();
+        fs::write(home.join("project/build.log"), "").unwrap();
+        fs::write(home.join("project/target/debug/app"), "").unwrap();
+        fs::write(home.join("project/src/main.rs"), "").unwrap();
+        fs::write(home.join("project/src/keep.log"), "").unwrap();
+        fs::write(home.join("project/src/deep/deeper/too-deep.rs"), "").unwrap();
+
+        let settings = FileIndexSettings {
+            roots: vec![home.clone()],
+            max_depth: 3,
+            include_hidden: false,
+            respect_ignore_files: true,
+        };
+        let index = FileIndex::new(&cache_dir, settings.clone(), Duration::ZERO);
+        let stats = index.refresh_now();
+        assert_eq!(stats.listed, 4);
+
+        let mut files: Vec<String> = index
+            .files()
+            .iter()
+            .map(|path| path.strip_prefix(&home).unwrap().to_string_lossy().to_string())
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["notes.md", "project/src/keep.log", "project/src/main.rs"]);
+
+        // A new index over the same cache lists nothing; editing an ignore file
+        // re-lists its directory and everything below it
+        let warm = FileIndex::new(&cache_dir, settings, Duration::ZERO);
+        assert_eq!(warm.refresh_now(), RefreshStats { listed: 0, reused: 4, removed: 0 });
+
+        std::thread::sleep(Duration::from_millis(10));
+        fs::write(home.join("project/.gitignore"), "*.log\n").unwrap();
+        let stats = warm.refresh_now();
+        assert_eq!((stats.listed, stats.reused), (5, 1));
+        assert!(warm.files().contains(&home.join("project/target/debug/app")));
+    }
+}
@@ -0,0 +1,570 @@
+// src/services/frecency.rs - Built-in frecency database of visited directories
+use crate::config;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+const FRECENCY_FORMAT_VERSION: u32 = 1;
+
+const HOUR: i64 = 60 * 60;
+const DAY: i64 = 24 * HOUR;
+const WEEK: i64 = 7 * DAY;
+
+/// A directory and how often and how recently it was visited
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DirEntry {
+    pub path: String,
+    /// Visit count, scaled down whenever the database is aged
+    pub rank: f64,
+    /// Unix timestamp of the last visit
+    pub last_accessed: i64,
+}
+
+impl DirEntry {
+    /// Rank weighted by recency, the same buckets zoxide uses
+    pub fn score(&self, now: i64) -> f64 {
+        let age = now - self.last_accessed;
+        let weight = if age < HOUR {
+            4.0
+        } else if age < DAY {
+            2.0
+        } else if age < WEEK {
+            0.5
+        } else {
+            0.25
+        };
+        self.rank * weight
+    }
+}
+
+/// Databases of other directory jumpers that can be imported once
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportSource {
+    Zoxide,
+    Autojump,
+    Fasd,
+}
+
+impl ImportSource {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "zoxide" => Some(Self::Zoxide),
+            "autojump" => Some(Self::Autojump),
+            "fasd" => Some(Self::Fasd),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Zoxide => "zoxide",
+            Self::Autojump => "autojump",
+            Self::Fasd => "fasd",
+        }
+    }
+
+    /// Every directory in the source's database
+    fn read(self, zoxide_path: &str, now: i64) -> Result<Vec<DirEntry>> {
+        let entries = match self {
+            Self::Zoxide => {
+                // The database has visit times; the CLI only lists scores
+                if let Some(entries) = read_zoxide_db() {
+                    return Ok(entries.to_vec());
+                }
+                let output = std::process::Command::new(zoxide_path)
+                    .args(["query", "--list", "--score"])
+                    .output()
+                    .context("Failed to run zoxide")?;
+                anyhow::ensure!(output.status.success(), "zoxide query failed");
+                parse_scored_paths(&String::from_utf8_lossy(&output.stdout))
+                    .into_iter()
+                    .map(|(path, rank)| DirEntry { path, rank, last_accessed: now })
+                    .collect()
+            }
+            Self::Autojump => {
+                let data_home = dirs::data_dir().context("No data directory")?;
+                let content = std::fs::read_to_string(data_home.join("autojump").join("autojump.txt"))
+                    .context("Failed to read autojump database")?;
+                parse_autojump(&content, now)
+            }
+            Self::Fasd => {
+                let file = std::env::var("_FASD_DATA").unwrap_or_else(|_| "~/.fasd".to_string());
+                let content = std::fs::read_to_string(shellexpand::tilde(&file).as_ref())
+                    .context("Failed to read fasd database")?;
+                parse_fasd(&content)
+            }
+        };
+        Ok(entries)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoreFile {
+    version: u32,
+    #[serde(default)]
+    imported: Vec<String>,
+    #[serde(default)]
+    dirs: Vec<DirEntry>,
+}
+
+/// Visited directories kept in `config_dir`, queried like zoxide when it isn't
+/// installed
+#[derive(Debug)]
+pub struct FrecencyStore {
+    dirs: HashMap<String, DirEntry>,
+    /// Sources already imported, so their counts aren't added twice
+    imported: Vec<String>,
+    /// Total rank above which all ranks are scaled down
+    max_age: f64,
+    file_path: PathBuf,
+    dirty: bool,
+}
+
+impl FrecencyStore {
+    pub fn load_from(path: &Path, max_age: f64) -> Self {
+        let file: StoreFile = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| match serde_json::from_str(&content) {
+                Ok(file) => Some(file),
+                Err(e) => {
+                    crate::utils::log_warn(&format!("Ignoring unreadable frecency database {}: {}", path.display(), e));
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        Self {
+            dirs: file.dirs.into_iter().map(|entry| (entry.path.clone(), entry)).collect(),
+            imported: file.imported,
+            max_age,
+            file_path: path.to_path_buf(),
+            dirty: false,
+        }
+    }
+
+    pub fn save(&mut self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        if let Some(parent) = self.file_path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create frecency database directory")?;
+        }
+
+        let mut dirs: Vec<DirEntry> = self.dirs.values().cloned().collect();
+        dirs.sort_by(|a, b| b.rank.total_cmp(&a.rank).then_with(|| a.path.cmp(&b.path)));
+        let file = StoreFile {
+            version: FRECENCY_FORMAT_VERSION,
+            imported: self.imported.clone(),
+            dirs,
+        };
+        let content = serde_json::to_string_pretty(&file).context("Failed to serialize frecency database")?;
+        std::fs::write(&self.file_path, content).context("Failed to write frecency database")?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Count a visit to `path` at `now`
+    pub fn add(&mut self, path: &str, now: i64) {
+        let path = normalize(path);
+        let entry = self.dirs.entry(path.clone()).or_insert(DirEntry {
+            path,
+            rank: 0.0,
+            last_accessed: now,
+        });
+        entry.rank += 1.0;
+        entry.last_accessed = now;
+        self.dirty = true;
+        self.age();
+    }
+
+    /// Merge another database's entries: ranks add up, the later visit wins
+    pub fn import(&mut self, source: &str, entries: Vec<DirEntry>) -> usize {
+        let count = entries.len();
+        for imported in entries {
+            let path = normalize(&imported.path);
+            match self.dirs.get_mut(&path) {
+                Some(entry) => {
+                    entry.rank += imported.rank;
+                    entry.last_accessed = entry.last_accessed.max(imported.last_accessed);
+                }
+                None => {
+                    self.dirs.insert(path.clone(), DirEntry { path, ..imported });
+                }
+            }
+        }
+        self.imported.push(source.to_string());
+        self.dirty = true;
+        self.age();
+        count
+    }
+
+    pub fn has_imported(&self, source: &str) -> bool {
+        self.imported.iter().any(|imported| imported == source)
+    }
+
+    /// Once the ranks add up to more than `max_age`, scale them down so the total is
+    /// 90% of it and forget directories that drop below one visit, as zoxide does
+    fn age(&mut self) {
+        let total: f64 = self.dirs.values().map(|entry| entry.rank).sum();
+        if total <= self.max_age {
+            return;
+        }
+
+        let factor = 0.9 * self.max_age / total;
+        self.dirs.retain(|_, entry| {
+            entry.rank *= factor;
+            entry.rank >= 1.0
+        });
+    }
+
+    /// Existing directories matching `keywords`, best first. Every keyword has to
+    /// appear in the path in order, and the last one in its final component.
+    pub fn query(&self, keywords: &[&str], now: i64) -> Vec<(&DirEntry, f64)> {
+        let keywords: Vec<String> = keywords.iter().map(|keyword| keyword.to_lowercase()).collect();
+        let mut matches: Vec<(&DirEntry, f64)> = self
+            .dirs
+            .values()
+            .filter(|entry| matches_keywords(&entry.path, &keywords))
+            .filter(|entry| Path::new(&entry.path).is_dir())
+            .map(|entry| (entry, entry.score(now)))
+            .collect();
+
+        matches.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.path.cmp(&b.0.path)));
+        matches
+    }
+
+    pub fn len(&self) -> usize {
+        self.dirs.len()
+    }
+}
+
+/// zoxide's keyword matching: the last keyword must match within the last path
+/// component, and the others must appear before it, right to left
+fn matches_keywords(path: &str, keywords: &[String]) -> bool {
+    let Some((last, rest)) = keywords.split_last() else {
+        return true;
+    };
+
+    let path = path.to_lowercase();
+    let Some(index) = path.rfind(last.as_str()) else {
+        return false;
+    };
+    if path[index + last.len()..].contains('/') {
+        return false;
+    }
+
+    let mut remaining = &path[..index];
+    for keyword in rest.iter().rev() {
+        match remaining.rfind(keyword.as_str()) {
+            Some(index) => remaining = &remaining[..index],
+            None => return false,
+        }
+    }
+    true
+}
+
+fn normalize(path: &str) -> String {
+    let trimmed = path.trim_end_matches('/');
+    if trimmed.is_empty() { "/" } else { trimmed }.to_string()
+}
+
+/// Lines of `<score> <path>`, as printed by `zoxide query --list --score`
+pub fn parse_scored_paths(output: &str) -> Vec<(String, f64)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (score, path) = line.trim_start().split_once(char::is_whitespace)?;
+            let path = path.trim();
+            Some((path.to_string(), score.parse().ok()?)).filter(|_| !path.is_empty())
+        })
+        .collect()
+}
+
+/// zoxide's database, `db.zo` in `_ZO_DATA_DIR` or the user's data directory
+pub fn zoxide_db_path() -> Option<PathBuf> {
+    std::env::var_os("_ZO_DATA_DIR")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::data_dir().map(|dir| dir.join("zoxide")))
+        .map(|dir| dir.join("db.zo"))
+}
+
+/// zoxide's directories with their visit times, if its database can be read
+pub fn read_zoxide_db() -> Option<Arc<Vec<DirEntry>>> {
+    read_zoxide_db_at(&zoxide_db_path()?)
+}
+
+/// The last zoxide database parsed, with the file it came from
+struct ZoxideDbSnapshot {
+    path: PathBuf,
+    modified: SystemTime,
+    entries: Arc<Vec<DirEntry>>,
+}
+
+static ZOXIDE_DB_CACHE: Mutex<Option<ZoxideDbSnapshot>> = Mutex::new(None);
+
+/// Parse the database at `path`, reusing the last parse while its modification
+/// time is unchanged
+fn read_zoxide_db_at(path: &Path) -> Option<Arc<Vec<DirEntry>>> {
+    let modified = std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()?;
+    let mut cache = ZOXIDE_DB_CACHE.lock().ok()?;
+    if let Some(snapshot) = cache.as_ref() {
+        if snapshot.path == path && snapshot.modified == modified {
+            return Some(Arc::clone(&snapshot.entries));
+        }
+    }
+
+    let entries = Arc::new(parse_zoxide_db(&std::fs::read(path).ok()?)?);
+    *cache = Some(ZoxideDbSnapshot {
+        path: path.to_path_buf(),
+        modified,
+        entries: Arc::clone(&entries),
+    });
+    Some(entries)
+}
+
+/// zoxide's database format 3: the version, then the directories encoded by
+/// bincode with fixed-width little-endian integers. Anything else is rejected.
+pub fn parse_zoxide_db(mut bytes: &[u8]) -> Option<Vec<DirEntry>> {
+    fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+        if bytes.len() < len {
+            return None;
+        }
+        let (head, rest) = bytes.split_at(len);
+        *bytes = rest;
+        Some(head)
+    }
+    fn u64_le(bytes: &mut &[u8]) -> Option<u64> {
+        take(bytes, 8).map(|b| u64::from_le_bytes(b.try_into().unwrap_or_default()))
+    }
+
+    let version = u32::from_le_bytes(take(&mut bytes, 4)?.try_into().ok()?);
+    if version != 3 {
+        return None;
+    }
+
+    let count = u64_le(&mut bytes)? as usize;
+    let mut entries = Vec::with_capacity(count.min(bytes.len()));
+    for _ in 0..count {
+        let len = u64_le(&mut bytes)? as usize;
+        let path = std::str::from_utf8(take(&mut bytes, len)?).ok()?.to_string();
+        let rank = f64::from_bits(u64_le(&mut bytes)?);
+        let last_accessed = u64_le(&mut bytes)? as i64;
+        entries.push(DirEntry { path, rank, last_accessed });
+    }
+    bytes.is_empty().then_some(entries)
+}
+
+/// autojump's `<weight>\t<path>` lines. It keeps no visit times.
+fn parse_autojump(content: &str, now: i64) -> Vec<DirEntry> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (weight, path) = line.split_once('\t')?;
+            Some(DirEntry {
+                path: path.trim().to_string(),
+                rank: weight.trim().parse().ok()?,
+                last_accessed: now,
+            })
+        })
+        .collect()
+}
+
+/// fasd's `<path>|<rank>|<timestamp>` lines, which also list files
+fn parse_fasd(content: &str) -> Vec<DirEntry> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.rsplitn(3, '|');
+            let last_accessed = fields.next()?.trim().parse().ok()?;
+            let rank = fields.next()?.trim().parse().ok()?;
+            let path = fields.next()?.to_string();
+            Some(DirEntry { path, rank, last_accessed })
+        })
+        .filter(|entry| Path::new(&entry.path).is_dir())
+        .collect()
+}
+
+// Shared store, loaded at startup
+static FRECENCY_STORE: OnceLock<Mutex<FrecencyStore>> = OnceLock::new();
+
+/// Load the frecency database and import the configured sources that haven't
+/// been imported yet
+pub fn init_frecency_store() -> Result<()> {
+    let config = config::get_config();
+    let settings = &config.providers.directories;
+    let mut store = FrecencyStore::load_from(&config.paths.config_dir.join("frecency.json"), settings.max_age);
+
+    let now = chrono::Utc::now().timestamp();
+    for name in &settings.import_from {
+        let Some(source) = ImportSource::parse(name) else {
+            crate::utils::log_warn(&format!("Unknown directory database to import: {}", name));
+            continue;
+        };
+        if store.has_imported(source.name()) {
+            continue;
+        }
+        match source.read(&settings.zoxide_path, now) {
+            Ok(entries) => {
+                let count = store.import(source.name(), entries);
+                crate::utils::log_info(&format!("Imported {} directories from {}", count, source.name()));
+            }
+            Err(e) => crate::utils::log_warn(&format!("Couldn't import directories from {}: {}", source.name(), e)),
+        }
+    }
+    if let Err(e) = store.save() {
+        crate::utils::log_error(&format!("Failed to save frecency database: {}", e));
+    }
+
+    FRECENCY_STORE
+        .set(Mutex::new(store))
+        .map_err(|_| anyhow::anyhow!("Frecency store already initialized"))?;
+    Ok(())
+}
+
+/// Count a visit to a directory opened through wayfindr
+pub fn record_visit(path: &str) {
+    let Some(Ok(mut store)) = FRECENCY_STORE.get().map(|store| store.lock()) else {
+        return;
+    };
+    store.add(path, chrono::Utc::now().timestamp());
+    if let Err(e) = store.save() {
+        crate::utils::log_error(&format!("Failed to save frecency database: {}", e));
+    }
+}
+
+/// Directories matching `keywords` with their frecency scores, best first
+pub fn query(keywords: &[&str]) -> Vec<(DirEntry, f64)> {
+    let Some(Ok(store)) = FRECENCY_STORE.get().map(|store| store.lock()) else {
+        return Vec::new();
+    };
+    store
+        .query(keywords, chrono::Utc::now().timestamp())
+        .into_iter()
+        .map(|(entry, score)| (entry.clone(), score))
+        .collect()
+}
+
+/// Frecency score of every known directory, from the built-in database and
+/// zoxide's, keeping the higher score for directories in both
+pub fn scores() -> HashMap<String, f64> {
+    let now = chrono::Utc::now().timestamp();
+    let mut scores: HashMap<String, f64> = HashMap::new();
+    let mut add = |entry: &DirEntry| {
+        let score = entry.score(now);
+        let known = scores.entry(entry.path.clone()).or_default();
+        *known = known.max(score);
+    };
+
+    if let Some(Ok(store)) = FRECENCY_STORE.get().map(|store| store.lock()) {
+        store.dirs.values().for_each(&mut add);
+    }
+    read_zoxide_db().unwrap_or_default().iter().for_each(add);
+    scores
+}
+
+/// Number of directories in the database, if it was loaded
+pub fn len() -> Option<usize> {
+    FRECENCY_STORE.get()?.lock().ok().map(|store| store.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A format 3 zoxide database holding one directory
+    fn zoxide_db(path: &str, rank: f64, last_accessed: i64) -> Vec<u8> {
+        let mut db = Vec::new();
+        db.extend(3u32.to_le_bytes());
+        db.extend(1u64.to_le_bytes());
+        db.extend((path.len() as u64).to_le_bytes());
+        db.extend(path.as_bytes());
+        db.extend(rank.to_le_bytes());
+        db.extend((last_accessed as u64).to_le_bytes());
+        db
+    }
+
+    #[test]
+    fn test_keywords_match_like_zoxide() {
+        let keywords = |query: &str| query.split_whitespace().map(str::to_string).collect::<Vec<_>>();
+        assert!(matches_keywords("/home/me/Dev/wayfindr", &keywords("dev way")));
+        assert!(matches_keywords("/home/me/Dev/wayfindr", &keywords("findr")));
+        assert!(!matches_keywords("/home/me/Dev/wayfindr", &keywords("way dev")));
+        assert!(!matches_keywords("/home/me/Dev/wayfindr/src", &keywords("way")));
+        assert!(matches_keywords("/home/me/Dev/wayfindr/src", &keywords("way src")));
+        assert!(matches_keywords("/anything", &[]));
+    }
+
+    #[test]
+    fn test_store_learns_ages_and_imports() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let project = root.join("Dev").join("wayfindr");
+        let other = root.join("Dev").join("waybar");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::create_dir_all(&other).unwrap();
+        let (project, other) = (project.to_string_lossy().to_string(), other.to_string_lossy().to_string());
+
+        let db = root.join("frecency.json");
+        let now = 1_700_000_000;
+        let mut store = FrecencyStore::load_from(&db, 10.0);
+        store.add(&project, now - 2 * WEEK);
+        store.add(&project, now - 2 * WEEK);
+        store.add(&project, now - 2 * WEEK);
+        store.add(&format!("{}/", other), now);
+        store.add(&root.join("gone").to_string_lossy(), now);
+
+        // A recent visit outweighs older, more frequent ones
+        let found: Vec<_> = store.query(&["dev", "WAY"], now).into_iter().map(|(e, _)| e.path.clone()).collect();
+        assert_eq!(found, vec![other.clone(), project.clone()]);
+
+        let fasd = format!("{}|7.5|{}\n{}/file.txt|3|{}\nnot a line\n", project, now, project, now);
+        assert_eq!(store.import("fasd", parse_fasd(&fasd)), 1);
+        assert!(store.has_imported("fasd"));
+        store.save().unwrap();
+
+        // The import took the total rank to 12.5, over the maximum of 10, so ranks
+        // were scaled by 0.72 and directories left below one visit were forgotten
+        let store = FrecencyStore::load_from(&db, 10.0);
+        assert_eq!(store.len(), 1);
+        assert!(store.has_imported("fasd"));
+        assert!((store.dirs[&project].rank - 10.5 * 0.72).abs() < 1e-9);
+        assert_eq!(store.query(&["findr"], now)[0].0.last_accessed, now);
+
+        assert_eq!(
+            parse_scored_paths("  12.5 /home/me/my projects\n 0.2 /tmp\nbogus\n"),
+            vec![("/home/me/my projects".to_string(), 12.5), ("/tmp".to_string(), 0.2)]
+        );
+        assert_eq!(parse_autojump("22.4\t/srv/www\n", now)[0].rank, 22.4);
+    }
+
+    #[test]
+    fn test_parse_zoxide_db() {
+        let now = 1_700_000_000;
+        let zoxide_db = zoxide_db("/srv/www", 6.5, now);
+        assert_eq!(
+            parse_zoxide_db(&zoxide_db),
+            Some(vec![DirEntry { path: "/srv/www".to_string(), rank: 6.5, last_accessed: now }])
+        );
+        assert_eq!(parse_zoxide_db(&zoxide_db[..zoxide_db.len() - 1]), None);
+    }
+
+    #[test]
+    fn test_zoxide_db_is_reparsed_only_when_modified() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let path = root.join("db.zo");
+
+        std::fs::write(&path, zoxide_db("/srv/www", 6.5, 1_700_000_000)).unwrap();
+        let first = read_zoxide_db_at(&path).unwrap();
+        assert!(Arc::ptr_eq(&first, &read_zoxide_db_at(&path).unwrap()));
+
+        std::fs::write(&path, zoxide_db("/srv/ftp", 2.0, 1_700_000_000)).unwrap();
+        let later = SystemTime::now() + std::time::Duration::from_secs(10);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert_eq!(read_zoxide_db_at(&path).unwrap()[0].path, "/srv/ftp");
+    }
+}
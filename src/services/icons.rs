@@ -0,0 +1,460 @@
+// src/services/icons.rs - freedesktop Icon Theme lookup for result icons
+use crate::providers::desktop_entry::DesktopFile;
+use crate::types::ActionResult;
+use crate::utils;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+const FALLBACK_THEME: &str = "hicolor";
+const ICON_EXTENSIONS: [&str; 3] = ["png", "svg", "xpm"];
+const CACHE_FILE_NAME: &str = "icons.json";
+const DEFAULT_ICON_SIZE: u32 = 48;
+
+/// One `[<subdir>]` group of an `index.theme`
+#[derive(Debug, Clone)]
+struct ThemeDirectory {
+    path: String,
+    size: u32,
+    scale: u32,
+    kind: DirectoryKind,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum DirectoryKind {
+    Fixed,
+    Scalable { min: u32, max: u32 },
+    Threshold(u32),
+}
+
+impl ThemeDirectory {
+    fn matches_size(&self, size: u32) -> bool {
+        if self.scale != 1 {
+            return false;
+        }
+        match self.kind {
+            DirectoryKind::Fixed => self.size == size,
+            DirectoryKind::Scalable { min, max } => (min..=max).contains(&size),
+            DirectoryKind::Threshold(threshold) => {
+                self.size.saturating_sub(threshold) <= size && size <= self.size + threshold
+            }
+        }
+    }
+
+    /// How far the directory's icons are from the wanted size, per the spec's
+    /// `DirectorySizeDistance`
+    fn size_distance(&self, size: u32) -> u32 {
+        let size = size as i64;
+        let scaled = |value: u32| value as i64 * self.scale as i64;
+        let (min, max) = match self.kind {
+            DirectoryKind::Fixed => (scaled(self.size), scaled(self.size)),
+            DirectoryKind::Scalable { min, max } => (scaled(min), scaled(max)),
+            DirectoryKind::Threshold(threshold) => (
+                scaled(self.size.saturating_sub(threshold)),
+                scaled(self.size + threshold),
+            ),
+        };
+        if size < min {
+            (min - size) as u32
+        } else if size > max {
+            (size - max) as u32
+        } else {
+            0
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct IconTheme {
+    /// Directories of this theme across the icon base directories
+    roots: Vec<PathBuf>,
+    inherits: Vec<String>,
+    directories: Vec<ThemeDirectory>,
+}
+
+impl IconTheme {
+    fn load(name: &str, base_dirs: &[PathBuf]) -> Option<Self> {
+        let roots: Vec<PathBuf> = base_dirs
+            .iter()
+            .map(|base| base.join(name))
+            .filter(|root| root.is_dir())
+            .collect();
+        let index = roots
+            .iter()
+            .find_map(|root| fs::read_to_string(root.join("index.theme")).ok())?;
+
+        let file = DesktopFile::parse(&index);
+        let theme = file.group("Icon Theme")?;
+        let comma_list = |key: &str| -> Vec<String> {
+            theme
+                .string(key)
+                .map(|value| {
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|item| !item.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        let mut directory_names = comma_list("Directories");
+        directory_names.extend(comma_list("ScaledDirectories"));
+
+        let directories = directory_names
+            .into_iter()
+            .filter_map(|path| {
+                let group = file.group(&path)?;
+                let number = |key: &str| group.string(key).and_then(|value| value.trim().parse::<u32>().ok());
+                let size = number("Size")?;
+                let kind = match group.string("Type").as_deref().map(str::trim) {
+                    Some("Fixed") => DirectoryKind::Fixed,
+                    Some("Scalable") => DirectoryKind::Scalable {
+                        min: number("MinSize").unwrap_or(size),
+                        max: number("MaxSize").unwrap_or(size),
+                    },
+                    _ => DirectoryKind::Threshold(number("Threshold").unwrap_or(2)),
+                };
+                Some(ThemeDirectory {
+                    path,
+                    size,
+                    scale: number("Scale").unwrap_or(1),
+                    kind,
+                })
+            })
+            .collect();
+
+        Some(Self {
+            roots,
+            inherits: comma_list("Inherits"),
+            directories,
+        })
+    }
+
+    /// An exact size match if there is one, otherwise the closest size
+    fn find(&self, icon: &str, size: u32) -> Option<PathBuf> {
+        let mut closest: Option<(u32, PathBuf)> = None;
+
+        for directory in &self.directories {
+            let distance = directory.size_distance(size);
+            if closest.as_ref().is_some_and(|(best, _)| *best <= distance) && !directory.matches_size(size) {
+                continue;
+            }
+
+            for root in &self.roots {
+                let Some(path) = find_with_extension(&root.join(&directory.path), icon) else {
+                    continue;
+                };
+                if directory.matches_size(size) {
+                    return Some(path);
+                }
+                if closest.as_ref().is_none_or(|(best, _)| distance < *best) {
+                    closest = Some((distance, path));
+                }
+            }
+        }
+
+        closest.map(|(_, path)| path)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IconCache {
+    theme: String,
+    size: u32,
+    icons: HashMap<String, PathBuf>,
+}
+
+/// Resolves icon names from desktop entries to files, following the freedesktop
+/// Icon Theme spec: the configured theme, the themes it inherits from, hicolor, and
+/// finally the unthemed pixmap directories.
+///
+/// Found icons are persisted in `cache_dir` by `save`; entries whose file has since
+/// disappeared are looked up again. Misses are only remembered for the session so
+/// that newly installed icons show up on the next start.
+pub struct IconResolver {
+    theme: String,
+    size: u32,
+    base_dirs: Vec<PathBuf>,
+    cache_file: Option<PathBuf>,
+    themes: Mutex<Option<Vec<IconTheme>>>,
+    cache: Mutex<IconCache>,
+    /// Whether `cache` has icons that aren't on disk yet
+    dirty: AtomicBool,
+    misses: Mutex<HashSet<String>>,
+}
+
+impl IconResolver {
+    pub fn new(theme: String, size: u32, base_dirs: Vec<PathBuf>, cache_file: Option<PathBuf>) -> Self {
+        let cache = cache_file
+            .as_deref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str::<IconCache>(&content).ok())
+            .filter(|cache| cache.theme == theme && cache.size == size)
+            .unwrap_or_else(|| IconCache {
+                theme: theme.clone(),
+                size,
+                icons: HashMap::new(),
+            });
+
+        Self {
+            theme,
+            size,
+            base_dirs,
+            cache_file,
+            themes: Mutex::new(None),
+            cache: Mutex::new(cache),
+            dirty: AtomicBool::new(false),
+            misses: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Path of the icon file for an `Icon=` value, which may already be a path
+    pub fn resolve(&self, icon: &str) -> Option<PathBuf> {
+        if icon.is_empty() {
+            return None;
+        }
+        if icon.starts_with('/') {
+            let path = PathBuf::from(icon);
+            return path.is_file().then_some(path);
+        }
+
+        if let Ok(cache) = self.cache.lock() {
+            if let Some(path) = cache.icons.get(icon).filter(|path| path.is_file()) {
+                return Some(path.clone());
+            }
+        }
+        if self.misses.lock().is_ok_and(|misses| misses.contains(icon)) {
+            return None;
+        }
+
+        let found = self.lookup(icon);
+        match &found {
+            Some(path) => {
+                if let Ok(mut cache) = self.cache.lock() {
+                    cache.icons.insert(icon.to_string(), path.clone());
+                    self.dirty.store(true, Ordering::Relaxed);
+                }
+            }
+            None => {
+                if let Ok(mut misses) = self.misses.lock() {
+                    misses.insert(icon.to_string());
+                }
+            }
+        }
+        found
+    }
+
+    fn lookup(&self, icon: &str) -> Option<PathBuf> {
+        let mut themes = self.themes.lock().unwrap_or_else(|e| e.into_inner());
+        let themes = themes.get_or_insert_with(|| self.load_theme_chain());
+
+        themes
+            .iter()
+            .find_map(|theme| theme.find(icon, self.size))
+            .or_else(|| {
+                self.base_dirs
+                    .iter()
+                    .find_map(|dir| find_with_extension(dir, icon))
+            })
+    }
+
+    /// The configured theme and everything it inherits from, breadth first, with
+    /// hicolor last
+    fn load_theme_chain(&self) -> Vec<IconTheme> {
+        let mut chain = Vec::new();
+        let mut visited: Vec<String> = Vec::new();
+        let mut pending = vec![self.theme.clone()];
+
+        while !pending.is_empty() {
+            let name = pending.remove(0);
+            if visited.contains(&name) || name == FALLBACK_THEME {
+                continue;
+            }
+            visited.push(name.clone());
+
+            match IconTheme::load(&name, &self.base_dirs) {
+                Some(theme) => {
+                    pending.extend(theme.inherits.iter().cloned());
+                    chain.push(theme);
+                }
+                None => utils::log_debug(&format!("Icon theme '{}' not found", name)),
+            }
+        }
+
+        chain.extend(IconTheme::load(FALLBACK_THEME, &self.base_dirs));
+        chain
+    }
+
+    /// Write icons resolved since the last save to the cache file
+    pub fn save(&self) {
+        if !self.dirty.swap(false, Ordering::Relaxed) {
+            return;
+        }
+        let Some(cache_file) = &self.cache_file else {
+            return;
+        };
+        let Ok(cache) = self.cache.lock() else {
+            return;
+        };
+        if let Some(parent) = cache_file.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        match serde_json::to_string(&*cache) {
+            Ok(content) => {
+                if let Err(e) = fs::write(cache_file, content) {
+                    utils::log_warn(&format!("Failed to write icon cache: {}", e));
+                }
+            }
+            Err(e) => utils::log_warn(&format!("Failed to serialize icon cache: {}", e)),
+        }
+    }
+}
+
+fn find_with_extension(dir: &Path, icon: &str) -> Option<PathBuf> {
+    ICON_EXTENSIONS
+        .iter()
+        .map(|extension| dir.join(format!("{}.{}", icon, extension)))
+        .find(|path| path.is_file())
+}
+
+/// Icon base directories in lookup order: `~/.icons`, `icons` in each XDG data
+/// directory, then `/usr/share/pixmaps`
+pub fn icon_base_dirs() -> Vec<PathBuf> {
+    let env_dir = |var: &str| std::env::var(var).ok().filter(|value| !value.is_empty());
+    let data_home = env_dir("XDG_DATA_HOME").unwrap_or_else(|| "~/.local/share".to_string());
+    let data_dirs = env_dir("XDG_DATA_DIRS").unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    let mut dirs = vec![PathBuf::from(shellexpand::tilde("~/.icons").as_ref())];
+    for dir in std::iter::once(data_home.as_str()).chain(data_dirs.split(':').filter(|dir| !dir.is_empty())) {
+        let dir = PathBuf::from(shellexpand::tilde(dir).as_ref()).join("icons");
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    dirs.push(PathBuf::from("/usr/share/pixmaps"));
+    dirs
+}
+
+/// The icon theme set for GTK applications, if any
+fn gtk_icon_theme() -> Option<String> {
+    ["~/.config/gtk-4.0/settings.ini", "~/.config/gtk-3.0/settings.ini"]
+        .iter()
+        .filter_map(|path| fs::read_to_string(shellexpand::tilde(path).as_ref()).ok())
+        .find_map(|content| {
+            content.lines().find_map(|line| {
+                let (key, value) = line.split_once('=')?;
+                (key.trim() == "gtk-icon-theme-name").then(|| value.trim().trim_matches('"').to_string())
+            })
+        })
+        .filter(|theme| !theme.is_empty())
+}
+
+static ICON_RESOLVER: OnceLock<IconResolver> = OnceLock::new();
+
+fn resolver() -> &'static IconResolver {
+    ICON_RESOLVER.get_or_init(|| {
+        let config = crate::config::try_get_config();
+        let theme = config
+            .and_then(|config| config.ui.icon_theme.clone())
+            .or_else(gtk_icon_theme)
+            .unwrap_or_else(|| FALLBACK_THEME.to_string());
+        let size = config.map_or(DEFAULT_ICON_SIZE, |config| config.ui.icon_size);
+        let cache_file = config.map(|config| config.paths.cache_dir.join(CACHE_FILE_NAME));
+
+        utils::log_debug(&format!("Resolving icons from theme '{}' at {}px", theme, size));
+        IconResolver::new(theme, size, icon_base_dirs(), cache_file)
+    })
+}
+
+/// Resolve an icon name to a file path with the configured theme. Unresolvable
+/// names are returned unchanged, since some consumers (like rofi) can still look
+/// them up themselves.
+pub fn resolve_icon(icon: &str) -> String {
+    resolver()
+        .resolve(icon)
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_else(|| icon.to_string())
+}
+
+/// Persist icons resolved since the last save, if any were looked up at all.
+/// Called once a search is done rather than for every icon.
+pub fn save_cache() {
+    if let Some(resolver) = ICON_RESOLVER.get() {
+        resolver.save();
+    }
+}
+
+/// Resolve the icon of a result and of its secondary actions in place
+pub fn resolve_result_icons(result: &mut ActionResult) {
+    if let Some(icon) = &result.metadata.icon {
+        result.metadata.icon = Some(resolve_icon(icon));
+    }
+    for action in &mut result.secondary_actions {
+        resolve_result_icons(action);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_lookup_follows_inheritance_and_prefers_matching_sizes() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let icons = root.join("icons");
+        let pixmaps = root.join("pixmaps");
+
+        write(
+            &icons.join("Custom/index.theme"),
+            "[Icon Theme]\nName=Custom\nInherits=Base\nDirectories=16x16/apps\n\n\
+             [16x16/apps]\nSize=16\nType=Fixed\n",
+        );
+        write(&icons.join("Custom/16x16/apps/firefox.png"), "");
+        write(
+            &icons.join("Base/index.theme"),
+            "[Icon Theme]\nName=Base\nDirectories=48x48/apps,scalable/apps\n\n\
+             [48x48/apps]\nSize=48\nType=Fixed\n\n\
+             [scalable/apps]\nSize=128\nType=Scalable\nMinSize=8\nMaxSize=512\n",
+        );
+        write(&icons.join("Base/48x48/apps/firefox.png"), "");
+        write(&icons.join("Base/scalable/apps/kitty.svg"), "");
+        write(
+            &icons.join("hicolor/index.theme"),
+            "[Icon Theme]\nName=Hicolor\nDirectories=48x48/apps\n\n[48x48/apps]\nSize=48\nType=Fixed\n",
+        );
+        write(&icons.join("hicolor/48x48/apps/code.png"), "");
+        write(&pixmaps.join("legacy.xpm"), "");
+
+        let cache_file = root.join("cache/icons.json");
+        let resolver = IconResolver::new(
+            "Custom".to_string(),
+            48,
+            vec![icons.clone(), pixmaps.clone()],
+            Some(cache_file.clone()),
+        );
+
+        // The first theme that has the icon wins, even at the wrong size
+        assert_eq!(resolver.resolve("firefox"), Some(icons.join("Custom/16x16/apps/firefox.png")));
+        assert_eq!(resolver.resolve("kitty"), Some(icons.join("Base/scalable/apps/kitty.svg")));
+        assert_eq!(resolver.resolve("code"), Some(icons.join("hicolor/48x48/apps/code.png")));
+        assert_eq!(resolver.resolve("legacy"), Some(pixmaps.join("legacy.xpm")));
+        assert_eq!(resolver.resolve("missing"), None);
+
+        // Hits survive a restart through the cache once saved, misses don't
+        assert!(!cache_file.exists());
+        resolver.save();
+        let cache: IconCache = serde_json::from_str(&fs::read_to_string(&cache_file).unwrap()).unwrap();
+        assert_eq!(cache.icons.len(), 4);
+    }
+}
@@ -0,0 +1,253 @@
+// src/services/mime.rs - MIME type guessing and mimeapps.list associations
+use crate::providers::{applications::DesktopApp, desktop_entry::{self, DesktopFile}};
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_GROUP: &str = "Default Applications";
+const ADDED_GROUP: &str = "Added Associations";
+const REMOVED_GROUP: &str = "Removed Associations";
+
+/// Bytes read from a file to sniff its type when the extension doesn't tell
+const SNIFF_LEN: usize = 512;
+
+/// Signatures of common formats that are often saved without an extension
+const MAGIC: [(&[u8], &str); 8] = [
+    (b"%PDF-", "application/pdf"),
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF8", "image/gif"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"\x7fELF", "application/x-executable"),
+    (b"#!", "application/x-shellscript"),
+];
+
+/// MIME type of `target`: `inode/directory` for directories, the URL scheme for
+/// URLs, then the file extension, then the first bytes of the file
+pub fn guess_mime_type(target: &str) -> String {
+    if let Some((scheme, _)) = target.split_once("://").filter(|(scheme, _)| *scheme != "file") {
+        return format!("x-scheme-handler/{}", scheme.to_ascii_lowercase());
+    }
+
+    let path = Path::new(target.strip_prefix("file://").unwrap_or(target));
+    if path.is_dir() {
+        return "inode/directory".to_string();
+    }
+    if let Some(mime) = mime_guess::from_path(path).first_raw() {
+        return mime.to_string();
+    }
+
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    let read = std::fs::File::open(path).and_then(|file| file.take(SNIFF_LEN as u64).read_to_end(&mut head));
+    match read {
+        Ok(_) => sniff(&head).to_string(),
+        Err(_) => "application/octet-stream".to_string(),
+    }
+}
+
+fn sniff(head: &[u8]) -> &'static str {
+    if head.is_empty() {
+        return "application/x-zerosize";
+    }
+    if let Some((_, mime)) = MAGIC.iter().find(|(magic, _)| head.starts_with(magic)) {
+        return mime;
+    }
+
+    // A multi-byte character may be cut off at the end of the sample
+    let text = match std::str::from_utf8(head) {
+        Ok(text) => Some(text),
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&head[..e.valid_up_to()]).ok(),
+        Err(_) => None,
+    };
+    match text {
+        Some(text) if !text.contains('\0') => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Defaults and associations merged from every `mimeapps.list`
+#[derive(Debug, Default)]
+pub struct MimeApps {
+    /// MIME type -> desktop IDs to try in order, from the most important file first
+    defaults: HashMap<String, Vec<String>>,
+    added: HashMap<String, Vec<String>>,
+    removed: HashMap<String, HashSet<String>>,
+}
+
+impl MimeApps {
+    /// Load the user's, the system's and the distribution's `mimeapps.list` files
+    pub fn load() -> Self {
+        Self::from_files(&mimeapps_paths())
+    }
+
+    /// Merge `files`, ordered from highest to lowest precedence
+    pub fn from_files(files: &[PathBuf]) -> Self {
+        let mut mime_apps = Self::default();
+        for path in files {
+            if let Ok(content) = std::fs::read_to_string(path) {
+                mime_apps.merge(&DesktopFile::parse(&content));
+            }
+        }
+        mime_apps
+    }
+
+    /// Add a lower-precedence file. Associations it adds that a more important file
+    /// already removed stay removed.
+    fn merge(&mut self, file: &DesktopFile) {
+        if let Some(group) = file.group(DEFAULT_GROUP) {
+            for mime in group.keys() {
+                self.defaults.entry(mime.to_string()).or_default().extend(group.list(mime));
+            }
+        }
+        if let Some(group) = file.group(ADDED_GROUP) {
+            for mime in group.keys() {
+                let removed = self.removed.get(mime);
+                let added = group.list(mime).into_iter().filter(|id| removed.is_none_or(|r| !r.contains(id)));
+                self.added.entry(mime.to_string()).or_default().extend(added);
+            }
+        }
+        if let Some(group) = file.group(REMOVED_GROUP) {
+            for mime in group.keys() {
+                self.removed.entry(mime.to_string()).or_default().extend(group.list(mime));
+            }
+        }
+    }
+
+    /// Apps that can open `mime`: the default first, then the user's added
+    /// associations, then every app listing the type in its `MimeType=` key
+    pub fn handlers<'a>(&self, mime: &str, apps: &'a [DesktopApp]) -> Vec<&'a DesktopApp> {
+        let find = |id: &String| apps.iter().find(|app| &app.desktop_id == id);
+        let removed = self.removed.get(mime);
+        let is_removed = |app: &DesktopApp| removed.is_some_and(|r| r.contains(&app.desktop_id));
+
+        let mut handlers: Vec<&DesktopApp> = Vec::new();
+        let mut push = |app: &'a DesktopApp| {
+            if !handlers.iter().any(|h| h.desktop_id == app.desktop_id) {
+                handlers.push(app);
+            }
+        };
+
+        // The first default that is installed wins
+        if let Some(default) = self.defaults.get(mime).and_then(|ids| ids.iter().find_map(find)) {
+            push(default);
+        }
+        for app in self.added.get(mime).into_iter().flatten().filter_map(find) {
+            push(app);
+        }
+        for app in apps.iter().filter(|app| !is_removed(app) && handles(app, mime)) {
+            push(app);
+        }
+
+        handlers
+    }
+
+    /// The default app for `mime`, if one is set and installed
+    pub fn default_for<'a>(&self, mime: &str, apps: &'a [DesktopApp]) -> Option<&'a DesktopApp> {
+        self.defaults
+            .get(mime)?
+            .iter()
+            .find_map(|id| apps.iter().find(|app| &app.desktop_id == id))
+    }
+}
+
+/// Whether the app declares `mime`, directly, through a `type/*` wildcard or, for
+/// text formats, through `text/plain`
+fn handles(app: &DesktopApp, mime: &str) -> bool {
+    let media_type = mime.split('/').next().unwrap_or(mime);
+    app.mime_types.iter().any(|declared| {
+        declared == mime
+            || declared.strip_suffix("/*").is_some_and(|prefix| prefix == media_type)
+            || (media_type == "text" && declared == "text/plain")
+    })
+}
+
+/// `mimeapps.list` locations from highest to lowest precedence: desktop-specific
+/// files before generic ones in each of the config and data directories
+pub fn mimeapps_paths() -> Vec<PathBuf> {
+    let env_dir = |var: &str| std::env::var(var).ok().filter(|value| !value.is_empty());
+    let config_home = env_dir("XDG_CONFIG_HOME").unwrap_or_else(|| "~/.config".to_string());
+    let config_dirs = env_dir("XDG_CONFIG_DIRS").unwrap_or_else(|| "/etc/xdg".to_string());
+
+    let mut dirs: Vec<PathBuf> = std::iter::once(config_home.as_str())
+        .chain(config_dirs.split(':').filter(|dir| !dir.is_empty()))
+        .map(|dir| PathBuf::from(shellexpand::tilde(dir).as_ref()))
+        .collect();
+    dirs.extend(desktop_entry::application_dirs().into_iter().rev());
+
+    let desktops: Vec<String> = desktop_entry::current_desktops()
+        .iter()
+        .map(|desktop| desktop.to_lowercase())
+        .collect();
+
+    dirs.iter()
+        .flat_map(|dir| {
+            desktops
+                .iter()
+                .map(|desktop| dir.join(format!("{}-mimeapps.list", desktop)))
+                .chain(std::iter::once(dir.join("mimeapps.list")))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(desktop_id: &str, mime_types: &str) -> DesktopApp {
+        let entry = format!(
+            "[Desktop Entry]\nType=Application\nName={}\nExec={} %F\nMimeType={}\n",
+            desktop_id, desktop_id, mime_types
+        );
+        DesktopApp::from_desktop_entry(desktop_id.to_string(), &entry, &[]).unwrap()
+    }
+
+    #[test]
+    fn test_handlers_follow_mimeapps_list() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+
+        let user = root.join("user-mimeapps.list");
+        std::fs::write(
+            &user,
+            "[Default Applications]\ntext/markdown=missing.desktop;code.desktop;\n\n\
+             [Added Associations]\ntext/markdown=typora.desktop;\n\n\
+             [Removed Associations]\ntext/markdown=gedit.desktop;\n",
+        )
+        .unwrap();
+        let system = root.join("system-mimeapps.list");
+        std::fs::write(
+            &system,
+            "[Default Applications]\ntext/markdown=gedit.desktop\n\n\
+             [Added Associations]\ntext/markdown=gedit.desktop;\n",
+        )
+        .unwrap();
+
+        let apps = vec![
+            app("gedit.desktop", "text/plain;"),
+            app("gimp.desktop", "image/png;image/*;"),
+            app("typora.desktop", ""),
+            app("code.desktop", "text/plain;inode/directory;"),
+            app("less.desktop", "text/plain;"),
+        ];
+        let mime_apps = MimeApps::from_files(&[user, system]);
+
+        let ids = |mime: &str| -> Vec<String> {
+            mime_apps.handlers(mime, &apps).iter().map(|app| app.desktop_id.clone()).collect()
+        };
+        assert_eq!(ids("text/markdown"), vec!["code.desktop", "typora.desktop", "less.desktop"]);
+        assert_eq!(mime_apps.default_for("text/markdown", &apps).unwrap().desktop_id, "code.desktop");
+        assert_eq!(ids("image/webp"), vec!["gimp.desktop"]);
+        assert_eq!(ids("inode/directory"), vec!["code.desktop"]);
+
+        let notes = root.join("NOTES");
+        std::fs::write(&notes, "plain text with ümlauts").unwrap();
+        let script = root.join("deploy");
+        std::fs::write(&script, "#!/bin/sh\necho hi\n").unwrap();
+        assert_eq!(guess_mime_type(&notes.to_string_lossy()), "text/plain");
+        assert_eq!(guess_mime_type(&script.to_string_lossy()), "application/x-shellscript");
+        assert_eq!(guess_mime_type(&root.to_string_lossy()), "inode/directory");
+        assert_eq!(guess_mime_type("/nonexistent/photo.JPG"), "image/jpeg");
+        assert_eq!(guess_mime_type("https://example.com"), "x-scheme-handler/https");
+    }
+}
@@ -1,6 +1,13 @@
 // src/services/mod.rs
 pub mod ai;
+pub mod app_index;
+pub mod arg_history;
 pub mod execution;
+pub mod file_index;
+pub mod frecency;
+pub mod icons;
+pub mod mime;
+pub mod navigate;
 pub mod usage;
 pub mod directory_autocomplete;
 
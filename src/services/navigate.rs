@@ -0,0 +1,149 @@
+// src/services/navigate.rs - Ways of opening a directory result
+use crate::config::{Config, NavigateHandlerConfig};
+use crate::utils;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Used when the configured default handler doesn't exist
+pub const FALLBACK_HANDLER: &str = "terminal";
+
+/// Text a `print` handler left for stdout, written once the interface has closed
+static PENDING_OUTPUT: Mutex<Option<String>> = Mutex::new(None);
+
+/// How a terminal is told where to start and what to run
+struct TerminalPreset {
+    /// Arguments that set the working directory; `{path}` is replaced
+    cwd_args: &'static str,
+    /// Arguments between the terminal and a command it should run
+    exec_args: &'static str,
+}
+
+/// Presets keyed by program name. Terminals missing here are started from a shell
+/// that has already changed into the directory.
+fn terminal_preset(program: &str) -> Option<TerminalPreset> {
+    let (cwd_args, exec_args) = match program {
+        "alacritty" => ("--working-directory {path}", "-e"),
+        "foot" | "footclient" => ("--working-directory={path}", ""),
+        "wezterm" => ("start --cwd {path}", "--"),
+        "kitty" => ("--directory {path}", ""),
+        "ghostty" => ("--working-directory={path}", "-e"),
+        "gnome-terminal" | "kgx" => ("--working-directory={path}", "--"),
+        "konsole" => ("--workdir {path}", "-e"),
+        "xfce4-terminal" => ("--working-directory={path}", "-x"),
+        "terminator" => ("--working-directory={path}", "-x"),
+        _ => return None,
+    };
+    Some(TerminalPreset { cwd_args, exec_args })
+}
+
+/// `terminal` (which may carry its own arguments) opened in `quoted_path`, with the
+/// arguments for running a command appended when `exec` is set
+fn terminal_command(terminal: &str, quoted_path: &str, exec: bool) -> String {
+    let program = terminal.split_whitespace().next().unwrap_or_default();
+    let program = Path::new(program).file_name().map_or(program.into(), |name| name.to_string_lossy());
+
+    let (command, exec_args) = match terminal_preset(&program) {
+        Some(preset) => (
+            format!("{} {}", terminal, preset.cwd_args.replace("{path}", quoted_path)),
+            preset.exec_args,
+        ),
+        None => (format!("cd {} && {}", quoted_path, terminal), "-e"),
+    };
+    match (exec, exec_args) {
+        (true, args) if !args.is_empty() => format!("{} {}", command, args),
+        _ => command,
+    }
+}
+
+/// Handlers available without any configuration
+fn builtin_handlers() -> BTreeMap<String, NavigateHandlerConfig> {
+    let handler = |label: &str, icon: &str, command: Option<&str>, print: Option<&str>| NavigateHandlerConfig {
+        label: label.to_string(),
+        icon: Some(icon.to_string()),
+        command: command.map(str::to_string),
+        print: print.map(str::to_string),
+    };
+
+    BTreeMap::from([
+        (
+            "terminal".to_string(),
+            handler("Open in terminal", "utilities-terminal", Some("{terminal}"), None),
+        ),
+        (
+            "file_manager".to_string(),
+            handler("Open in file manager", "system-file-manager", Some("xdg-open {path}"), None),
+        ),
+        (
+            "editor".to_string(),
+            handler("Open in editor", "accessories-text-editor", Some("{terminal_exec} ${EDITOR:-vi} {path}"), None),
+        ),
+        (
+            "shell".to_string(),
+            handler("cd in the calling shell", "utilities-terminal", None, Some("cd {path}")),
+        ),
+    ])
+}
+
+/// Built-in handlers with the configured ones added or replacing them by name
+pub fn handlers(config: &Config) -> BTreeMap<String, NavigateHandlerConfig> {
+    let mut handlers = builtin_handlers();
+    handlers.extend(config.navigate.handlers.clone());
+    handlers
+}
+
+/// Fill in a handler template for `path`
+pub fn render(template: &str, path: &str, terminal: &str) -> String {
+    let quoted_path = utils::shell_quote(path);
+    template
+        .replace("{terminal_exec}", &terminal_command(terminal, &quoted_path, true))
+        .replace("{terminal}", &terminal_command(terminal, &quoted_path, false))
+        .replace("{path}", &quoted_path)
+}
+
+/// Keep `output` to print once wayfindr exits
+pub fn set_pending_output(output: String) {
+    if let Ok(mut pending) = PENDING_OUTPUT.lock() {
+        *pending = Some(output);
+    }
+}
+
+pub fn take_pending_output() -> Option<String> {
+    PENDING_OUTPUT.lock().ok()?.take()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terminal_presets_set_the_working_directory() {
+        let path = "/home/me/it's here";
+        assert_eq!(
+            render("{terminal}", path, "alacritty"),
+            r"alacritty --working-directory '/home/me/it'\''s here'"
+        );
+        assert_eq!(render("{terminal}", "/tmp", "/usr/bin/foot"), "/usr/bin/foot --working-directory=/tmp");
+        assert_eq!(render("{terminal}", "/tmp", "wezterm"), "wezterm start --cwd /tmp");
+        assert_eq!(render("{terminal}", "/tmp", "st -f mono"), "cd /tmp && st -f mono");
+
+        assert_eq!(
+            render("{terminal_exec} ${EDITOR:-vi} {path}", "/tmp", "wezterm"),
+            "wezterm start --cwd /tmp -- ${EDITOR:-vi} /tmp"
+        );
+        assert_eq!(render("{terminal_exec} htop", "/tmp", "kitty"), "kitty --directory /tmp htop");
+
+        let mut config = Config::default();
+        config.navigate.handlers.insert(
+            "editor".to_string(),
+            NavigateHandlerConfig {
+                label: "Open in VS Code".to_string(),
+                command: Some("code {path}".to_string()),
+                ..Default::default()
+            },
+        );
+        let handlers = handlers(&config);
+        assert_eq!(handlers["editor"].label, "Open in VS Code");
+        assert_eq!(handlers["shell"].print.as_deref(), Some("cd {path}"));
+    }
+}
@@ -8,6 +8,10 @@ use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
 
+/// Version 2 switched to stable result IDs; older files are migrated on startup
+const USAGE_FORMAT_VERSION: u32 = 2;
+const VERSION_HEADER: &str = "# Version:";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsageEntry {
     pub count: u32,
@@ -35,6 +39,8 @@ pub struct UsageService {
     entries: HashMap<String, UsageEntry>,
     file_path: std::path::PathBuf,
     dirty: bool,
+    /// Format of the file on disk, `USAGE_FORMAT_VERSION` once saved
+    version: u32,
 }
 
 impl UsageService {
@@ -46,6 +52,7 @@ impl UsageService {
             entries: HashMap::new(),
             file_path,
             dirty: false,
+            version: USAGE_FORMAT_VERSION,
         };
 
         service.load()?;
@@ -61,10 +68,17 @@ impl UsageService {
         let file = File::open(&self.file_path).context("Failed to open usage stats file")?;
         let reader = BufReader::new(file);
 
+        // Files written before the version header are version 1
+        self.version = 1;
+
         for line in reader.lines() {
             let line = line.context("Failed to read line from usage stats")?;
             let line = line.trim();
 
+            if let Some(version) = line.strip_prefix(VERSION_HEADER) {
+                self.version = version.trim().parse().unwrap_or(1);
+                continue;
+            }
             if line.is_empty() || line.starts_with('#') {
                 continue;
             }
@@ -107,11 +121,12 @@ impl UsageService {
 
         writeln!(file, "# Wayfindr Usage Statistics")?;
         writeln!(file, "# Format: action_id|json_data")?;
+        writeln!(file, "{} {}", VERSION_HEADER, self.version)?;
         writeln!(file)?;
 
         // Sort by usage count (descending) for better readability
         let mut sorted_entries: Vec<_> = self.entries.iter().collect();
-        sorted_entries.sort_by(|a, b| b.1.count.cmp(&a.1.count));
+        sorted_entries.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.count));
 
         for (key, entry) in sorted_entries {
             let json_data =
@@ -167,24 +182,7 @@ impl UsageService {
         }
     }
 
-    pub fn get_top_used(&self, limit: usize) -> Vec<(&String, &UsageEntry)> {
-        let mut entries: Vec<_> = self.entries.iter().collect();
-        entries.sort_by(|a, b| b.1.count.cmp(&a.1.count));
-        entries.truncate(limit);
-        entries
-    }
-
-    pub fn get_top_used_with_counts(&self, limit: usize) -> Vec<(String, u32)> {
-        let mut entries: Vec<_> = self
-            .entries
-            .iter()
-            .map(|(id, entry)| (id.clone(), entry.count))
-            .collect();
-        entries.sort_by(|a, b| b.1.cmp(&a.1));
-        entries.truncate(limit);
-        entries
-    }
-
+    #[allow(dead_code, reason = "usage maintenance API, not wired to the CLI yet")]
     pub fn cleanup_old_entries(&mut self, days: i64) {
         let cutoff = Utc::now() - chrono::Duration::days(days);
         let initial_count = self.entries.len();
@@ -200,6 +198,50 @@ impl UsageService {
         }
     }
 
+    /// Move entries from old IDs to new ones, merging with any history already under
+    /// the new ID. Returns how many entries were moved.
+    pub fn migrate_ids(&mut self, renames: &[(String, String)]) -> usize {
+        let mut migrated = 0;
+
+        for (old_id, new_id) in renames {
+            if old_id == new_id {
+                continue;
+            }
+            let Some(old) = self.entries.remove(old_id) else {
+                continue;
+            };
+
+            match self.entries.get_mut(new_id) {
+                Some(entry) => {
+                    entry.count += old.count;
+                    entry.last_used = entry.last_used.max(old.last_used);
+                    entry.first_used = entry.first_used.min(old.first_used);
+                }
+                None => {
+                    self.entries.insert(new_id.clone(), old);
+                }
+            }
+            migrated += 1;
+        }
+
+        if migrated > 0 {
+            self.dirty = true;
+        }
+        migrated
+    }
+
+    /// Apply `legacy_ids` and mark the file as current if they were complete. Returns
+    /// how many entries were moved.
+    fn migrate_legacy_ids(&mut self, legacy_ids: &LegacyIds) -> usize {
+        let migrated = self.migrate_ids(&legacy_ids.renames);
+        if legacy_ids.complete {
+            self.version = USAGE_FORMAT_VERSION;
+            self.dirty = true;
+        }
+        migrated
+    }
+
+    #[allow(dead_code, reason = "usage maintenance API, not wired to the CLI yet")]
     pub fn reset_usage(&mut self, action_id: &str) -> bool {
         let removed = self.entries.remove(action_id).is_some();
         if removed {
@@ -208,6 +250,7 @@ impl UsageService {
         removed
     }
 
+    #[allow(dead_code, reason = "usage maintenance API, not wired to the CLI yet")]
     pub fn clear_all(&mut self) {
         let count = self.entries.len();
         self.entries.clear();
@@ -251,6 +294,44 @@ pub fn record_usage(action_id: &str) {
     }
 }
 
+/// Renames for `migrate_legacy_ids`, gathered from the providers
+pub struct LegacyIds {
+    /// (old ID, new ID) pairs
+    pub renames: Vec<(String, String)>,
+    /// False when a source of old IDs was unavailable, so unmatched entries may still
+    /// be claimed on a later start
+    pub complete: bool,
+}
+
+/// Rewrite a pre-version-2 usage file to the current result IDs. The file is only
+/// marked as migrated once every source of old IDs could be asked; until then the
+/// migration is retried on each start, and `legacy_ids` is only called while it's due.
+pub fn migrate_legacy_ids(legacy_ids: impl FnOnce() -> LegacyIds) {
+    let Some(Ok(mut service)) = USAGE_SERVICE.get().map(|service| service.lock()) else {
+        return;
+    };
+    if service.version >= USAGE_FORMAT_VERSION {
+        return;
+    }
+
+    let legacy_ids = legacy_ids();
+    let migrated = service.migrate_legacy_ids(&legacy_ids);
+    if !service.dirty {
+        return;
+    }
+    match service.save() {
+        Ok(()) if legacy_ids.complete => crate::utils::log_info(&format!(
+            "Migrated usage stats to format version {} ({} entries renamed)",
+            USAGE_FORMAT_VERSION, migrated
+        )),
+        Ok(()) => crate::utils::log_info(&format!(
+            "Partially migrated usage stats ({} entries renamed), retrying on the next start",
+            migrated
+        )),
+        Err(e) => crate::utils::log_error(&format!("Failed to save migrated usage stats: {}", e)),
+    }
+}
+
 pub fn get_usage_boost(action_id: &str) -> i32 {
     USAGE_SERVICE
         .get()
@@ -259,18 +340,11 @@ pub fn get_usage_boost(action_id: &str) -> i32 {
         .unwrap_or(0)
 }
 
-pub fn get_top_used(limit: usize) -> Vec<String> {
+pub fn get_usage_entry(action_id: &str) -> Option<UsageEntry> {
     USAGE_SERVICE
         .get()
         .and_then(|service| service.lock().ok())
-        .map(|service| {
-            service
-                .get_top_used(limit)
-                .into_iter()
-                .map(|(id, _)| id.clone())
-                .collect()
-        })
-        .unwrap_or_default()
+        .and_then(|service| service.entries.get(action_id).cloned())
 }
 
 pub fn get_usage_count(action_id: &str) -> u32 {
@@ -281,10 +355,91 @@ pub fn get_usage_count(action_id: &str) -> u32 {
         .unwrap_or(0)
 }
 
-pub fn get_top_used_with_counts(limit: usize) -> Vec<(String, u32)> {
-    USAGE_SERVICE
-        .get()
-        .and_then(|service| service.lock().ok())
-        .map(|service| service.get_top_used_with_counts(limit))
-        .unwrap_or_default()
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migration_merges_into_existing_entries() {
+        let now = Utc::now();
+        let earlier = now - chrono::Duration::days(3);
+        let entry = |count, first_used, last_used| UsageEntry { count, first_used, last_used };
+
+        let temp = tempfile::tempdir().unwrap();
+        let file_path = temp.path().join("usage.txt");
+        let mut service = UsageService {
+            entries: HashMap::from([
+                ("app_5f1d".to_string(), entry(5, earlier, earlier)),
+                ("app:firefox.desktop".to_string(), entry(2, now, now)),
+                ("app_77aa".to_string(), entry(1, now, now)),
+                ("dir_unknown".to_string(), entry(3, now, now)),
+            ]),
+            file_path: file_path.clone(),
+            dirty: false,
+            version: 1,
+        };
+
+        let renames = vec![
+            ("app_5f1d".to_string(), "app:firefox.desktop".to_string()),
+            ("app_77aa".to_string(), "app:kitty.desktop".to_string()),
+            ("app_gone".to_string(), "app:gone.desktop".to_string()),
+        ];
+        assert_eq!(service.migrate_ids(&renames), 2);
+
+        let firefox = &service.entries["app:firefox.desktop"];
+        assert_eq!((firefox.count, firefox.first_used, firefox.last_used), (7, earlier, now));
+        assert_eq!(service.entries["app:kitty.desktop"].count, 1);
+        // IDs nobody claims are kept as they are
+        assert_eq!(service.entries["dir_unknown"].count, 3);
+        assert_eq!(service.entries.len(), 3);
+
+        service.version = USAGE_FORMAT_VERSION;
+        service.save().unwrap();
+        let mut reloaded = UsageService {
+            entries: HashMap::new(),
+            file_path: file_path.clone(),
+            dirty: false,
+            version: USAGE_FORMAT_VERSION,
+        };
+        reloaded.load().unwrap();
+        assert_eq!(reloaded.version, USAGE_FORMAT_VERSION);
+        assert_eq!(reloaded.entries.len(), 3);
+    }
+
+    #[test]
+    fn test_incomplete_migration_is_retried() {
+        let now = Utc::now();
+        let entry = |count| UsageEntry { count, first_used: now, last_used: now };
+        let temp = tempfile::tempdir().unwrap();
+        let mut service = UsageService {
+            entries: HashMap::from([
+                ("app_5f1d".to_string(), entry(5)),
+                ("dir_9c02".to_string(), entry(3)),
+            ]),
+            file_path: temp.path().join("usage.txt"),
+            dirty: false,
+            version: 1,
+        };
+
+        // zoxide wasn't available, so only the apps were renamed
+        let apps_only = LegacyIds {
+            renames: vec![("app_5f1d".to_string(), "app:firefox.desktop".to_string())],
+            complete: false,
+        };
+        assert_eq!(service.migrate_legacy_ids(&apps_only), 1);
+        assert_eq!(service.version, 1);
+        assert_eq!(service.entries["dir_9c02"].count, 3);
+
+        let everything = LegacyIds {
+            renames: vec![
+                ("app_5f1d".to_string(), "app:firefox.desktop".to_string()),
+                ("dir_9c02".to_string(), "dir_00000000deadbeef".to_string()),
+            ],
+            complete: true,
+        };
+        assert_eq!(service.migrate_legacy_ids(&everything), 1);
+        assert_eq!(service.version, USAGE_FORMAT_VERSION);
+        assert_eq!(service.entries["app:firefox.desktop"].count, 5);
+        assert_eq!(service.entries["dir_00000000deadbeef"].count, 3);
+    }
+}
@@ -2,24 +2,40 @@
 
 use anyhow::{Context, Result as AnyhowResult};
 use crossterm::{
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    event::{KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags},
+    terminal::{
+        disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
     ExecutableCommand,
 };
-use std::io::stdout;
+use std::io::stderr;
 
+/// The TUI draws on stderr, leaving stdout free for output meant for the calling
+/// shell, e.g. `eval "$(wayfindr)"`
 pub fn setup_terminal() -> AnyhowResult<()> {
     enable_raw_mode().context("Failed to enable raw mode")?;
-    stdout()
+    stderr()
         .execute(EnterAlternateScreen)
         .context("Failed to enter alternate screen")?;
+
+    // Lets terminals that support it report Shift+Enter apart from Enter
+    if supports_keyboard_enhancement().unwrap_or(false) {
+        stderr()
+            .execute(PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES))
+            .context("Failed to enable keyboard enhancements")?;
+    }
     Ok(())
 }
 
 pub fn restore_terminal() -> AnyhowResult<()> {
+    if supports_keyboard_enhancement().unwrap_or(false) {
+        let _ = stderr().execute(PopKeyboardEnhancementFlags);
+    }
     if crossterm::terminal::is_raw_mode_enabled()? {
         disable_raw_mode().context("Failed to disable raw mode")?;
     }
-    stdout()
+    stderr()
         .execute(LeaveAlternateScreen)
         .context("Failed to leave alternate screen")?;
     Ok(())
@@ -11,13 +11,25 @@ pub struct ActionResult {
     pub description: String,
     pub data: ActionData,
     pub metadata: ActionMetadata,
+    /// Alternative actions on the same item, such as an app's "New Private Window"
+    #[serde(default)]
+    pub secondary_actions: Vec<ActionResult>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
 pub enum ActionType {
     Launch { needs_terminal: bool },
-    Navigate { path: String },
+    Navigate {
+        path: String,
+        /// Entry of `navigate.handlers` to open it with instead of the default one
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        handler: Option<String>,
+    },
+    /// Open a file with the default app for its MIME type
+    Open { path: String },
+    /// Bring an open window to the front
+    Focus { address: String },
     AiResponse,
     Custom { action_id: String },
 }
@@ -26,6 +38,12 @@ pub enum ActionType {
 #[serde(untagged)]
 pub enum ActionData {
     Command(String),
+    /// A program and its arguments; only quoted for the shell when launched
+    Argv {
+        argv: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        working_dir: Option<String>,
+    },
     Path(String),
     Text(String),
     Custom(serde_json::Value),
@@ -38,17 +56,27 @@ pub struct ActionMetadata {
     pub tags: Vec<String>,
     pub usage_count: u32,
     pub last_used: Option<chrono::DateTime<chrono::Utc>>,
+    /// Desktop file ID (e.g. `firefox.desktop`) for results backed by a desktop entry
+    #[serde(default)]
+    pub desktop_id: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 pub enum SearchMessage {
-    Query {
-        query: String,
-        provider_id: Option<String>,
+    /// Results from a single provider, sent as soon as that provider finishes
+    Batch {
+        generation: u64,
+        batch: crate::providers::ProviderBatch,
     },
-    Results(Vec<crate::providers::ScoredResult>),
-    Error(String),
-    Loading(bool),
+}
+
+impl SearchMessage {
+    /// The search generation this message answers
+    pub fn generation(&self) -> u64 {
+        match self {
+            SearchMessage::Batch { generation, .. } => *generation,
+        }
+    }
 }
 
 #[derive(Error, Debug)]
@@ -67,9 +95,6 @@ pub enum AppError {
 
     #[error("Provider error: {0}")]
     Provider(#[from] ProviderError),
-
-    #[error("Search error: {0}")]
-    Search(String),
 }
 
 #[derive(Error, Debug)]
@@ -113,6 +138,7 @@ impl ActionResult {
             description: String::new(),
             data: ActionData::Command(command.into()),
             metadata: ActionMetadata::default(),
+            secondary_actions: Vec::new(),
         }
     }
 
@@ -126,11 +152,15 @@ impl ActionResult {
         Self {
             id: id.into(),
             provider: provider.into(),
-            action: ActionType::Navigate { path: path.clone() },
+            action: ActionType::Navigate {
+                path: path.clone(),
+                handler: None,
+            },
             title: title.into(),
             description: String::new(),
             data: ActionData::Path(path),
             metadata: ActionMetadata::default(),
+            secondary_actions: Vec::new(),
         }
     }
 
@@ -147,6 +177,7 @@ impl ActionResult {
             description: String::new(),
             data: ActionData::Text(response.into()),
             metadata: ActionMetadata::default(),
+            secondary_actions: Vec::new(),
         }
     }
 
@@ -155,8 +186,45 @@ impl ActionResult {
         self
     }
 
+    pub fn with_data(mut self, data: ActionData) -> Self {
+        self.data = data;
+        self
+    }
+
     pub fn with_metadata(mut self, metadata: ActionMetadata) -> Self {
         self.metadata = metadata;
         self
     }
+
+    /// Append `args` to the launched command. Command lines get them shell-quoted;
+    /// results that don't launch anything are returned unchanged.
+    pub fn with_arguments(mut self, args: &[String]) -> Self {
+        match &mut self.data {
+            ActionData::Command(command) if !args.is_empty() => {
+                command.push(' ');
+                command.push_str(&crate::utils::shell_join(args));
+            }
+            ActionData::Argv { argv, .. } => argv.extend(args.iter().cloned()),
+            _ => {}
+        }
+        self
+    }
+
+    /// The launch that ad-hoc arguments would be added to: the result itself, or for
+    /// a result that focuses an open window, its "new instance" action
+    pub fn argument_target(&self) -> Option<&ActionResult> {
+        match (&self.action, &self.data) {
+            (ActionType::Launch { .. }, ActionData::Command(_) | ActionData::Argv { .. }) => Some(self),
+            (ActionType::Focus { .. }, _) => self.secondary_actions.iter().find_map(ActionResult::argument_target),
+            _ => None,
+        }
+    }
+
+    /// The file, directory or URL the result points at, which other apps can open
+    pub fn open_target(&self) -> Option<&str> {
+        match &self.action {
+            ActionType::Navigate { path, .. } | ActionType::Open { path } => Some(path),
+            _ => None,
+        }
+    }
 }
@@ -1,5 +1,5 @@
 // src/ui.rs
-use crate::app::{App, FocusState};
+use crate::app::{App, ArgumentEntry, FocusState};
 use ratatui::{
     prelude::*,
     widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
@@ -15,8 +15,16 @@ pub fn render(frame: &mut Frame, app: &App) {
         ])
         .split(frame.area());
 
-    render_input(frame, app, main_layout[0]);
-    render_results(frame, app, main_layout[1]);
+    match &app.arg_entry {
+        Some(entry) => {
+            render_argument_input(frame, entry, main_layout[0]);
+            render_argument_completions(frame, entry, main_layout[1]);
+        }
+        None => {
+            render_input(frame, app, main_layout[0]);
+            render_results(frame, app, main_layout[1]);
+        }
+    }
     render_status_bar(frame, app, main_layout[2]);
 
     // Render error popup if there's an error
@@ -51,6 +59,53 @@ fn render_input(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(input_paragraph, area);
 }
 
+fn render_argument_input(frame: &mut Frame, entry: &ArgumentEntry, area: Rect) {
+    let input_text = if entry.input.is_empty() {
+        "Type arguments to append, quoted as in a shell..."
+    } else {
+        &entry.input
+    };
+
+    let input_paragraph = Paragraph::new(format!("> {}", input_text))
+        .style(Style::default().fg(Color::Yellow))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Arguments for {}", entry.result.title))
+                .border_style(Style::default().fg(Color::Green)),
+        );
+
+    frame.render_widget(input_paragraph, area);
+}
+
+fn render_argument_completions(frame: &mut Frame, entry: &ArgumentEntry, area: Rect) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Previous arguments ({})", entry.completions.len()))
+        .border_style(Style::default().fg(Color::Gray));
+
+    if entry.completions.is_empty() {
+        let empty_paragraph = Paragraph::new("No earlier arguments for this result.")
+            .style(Style::default().fg(Color::DarkGray))
+            .block(block);
+        frame.render_widget(empty_paragraph, area);
+        return;
+    }
+
+    let items: Vec<ListItem> = entry
+        .completions
+        .iter()
+        .map(|args| ListItem::new(args.as_str()).style(Style::default().fg(Color::White)))
+        .collect();
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+
+    let mut list_state = ListState::default();
+    list_state.select(entry.selected);
+    frame.render_stateful_widget(list, area, &mut list_state);
+}
+
 fn render_results(frame: &mut Frame, app: &App, area: Rect) {
     let border_style = if app.focus == FocusState::Results {
         Style::default().fg(Color::Green)
@@ -58,12 +113,25 @@ fn render_results(frame: &mut Frame, app: &App, area: Rect) {
         Style::default().fg(Color::Gray)
     };
 
-    let results_block = Block::default()
+    let title = match &app.parent_results {
+        Some((_, _, parent_title)) => format!("Actions for {} ({})", parent_title, app.results.len()),
+        None => format!("Results ({})", app.results.len()),
+    };
+    let mut results_block = Block::default()
         .borders(Borders::ALL)
-        .title(format!("Results ({})", app.results.len()))
+        .title(title)
         .border_style(border_style);
 
-    if app.is_loading {
+    // One loading indicator per provider that hasn't reported back yet
+    if app.is_loading() {
+        results_block = results_block.title(
+            Line::from(format!(" ⏳ {} ", app.loading_providers.join(", ")))
+                .style(Style::default().fg(Color::Cyan))
+                .right_aligned(),
+        );
+    }
+
+    if app.results.is_empty() && app.is_loading() {
         let loading_paragraph = Paragraph::new("🔍 Searching...")
             .style(Style::default().fg(Color::Cyan))
             .block(results_block);
@@ -124,6 +192,22 @@ fn render_results_list(frame: &mut Frame, app: &App, area: Rect, block: Block) {
 fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
     let mut status_parts = Vec::new();
 
+    if app.arg_entry.is_some() {
+        status_parts.push("ESC:Cancel".to_string());
+        status_parts.push("↑↓:Previous".to_string());
+        status_parts.push("TAB:Complete".to_string());
+        status_parts.push("Enter:Launch".to_string());
+    } else {
+        render_controls(app, &mut status_parts);
+    }
+
+    let status_text = status_parts.join(" | ");
+    let status_paragraph = Paragraph::new(status_text).style(Style::default().fg(Color::DarkGray));
+
+    frame.render_widget(status_paragraph, area);
+}
+
+fn render_controls(app: &App, status_parts: &mut Vec<String>) {
     // Focus indicator
     let focus_text = match app.focus {
         FocusState::Input => "INPUT",
@@ -142,10 +226,17 @@ fn render_status_bar(frame: &mut Frame, app: &App, area: Rect) {
     status_parts.push("↑↓:Navigate".to_string());
     status_parts.push("Enter:Select".to_string());
 
-    let status_text = status_parts.join(" | ");
-    let status_paragraph = Paragraph::new(status_text).style(Style::default().fg(Color::DarkGray));
-
-    frame.render_widget(status_paragraph, area);
+    let selected = app.results.get(app.selected_index).filter(|_| app.focus == FocusState::Results);
+    if app.parent_results.is_some() {
+        status_parts.push("←:Back".to_string());
+    } else if selected.is_some_and(|result| !result.secondary_actions.is_empty()) {
+        status_parts.push("→:Actions".to_string());
+    } else if selected.is_some_and(|result| result.open_target().is_some()) {
+        status_parts.push("→:Open with".to_string());
+    }
+    if selected.is_some_and(|result| result.argument_target().is_some()) {
+        status_parts.push("Shift+Enter:Arguments".to_string());
+    }
 }
 
 fn render_error_popup(frame: &mut Frame, error_message: &str) {
@@ -177,6 +268,8 @@ fn get_result_icon(result: &crate::types::ActionResult) -> &'static str {
             needs_terminal: false,
         } => "🚀",
         ActionType::Navigate { .. } => "📁",
+        ActionType::Open { .. } => "📄",
+        ActionType::Focus { .. } => "🪟",
         ActionType::AiResponse => "🤖",
         ActionType::Custom { .. } => "⚙️",
     }
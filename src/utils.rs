@@ -1,11 +1,9 @@
 // src/utils.rs
-use crate::config::{get_config, LogLevel};
+use crate::config::{try_get_config, LogLevel};
 use chrono::Local;
 use std::fs::OpenOptions;
 use std::io::Write;
 
-pub const DEFAULT_LOG_FILE_PATH: &str = "~/.wayfindr/wayfindr.log";
-
 pub fn log_debug(message: &str) {
     log_with_level(LogLevel::Debug, message);
 }
@@ -23,7 +21,9 @@ pub fn log_error(message: &str) {
 }
 
 fn log_with_level(level: LogLevel, message: &str) {
-    let config = get_config();
+    let Some(config) = try_get_config() else {
+        return;
+    };
 
     // Check if we should log this level
     let should_log = match (&config.general.log_level, &level) {
@@ -65,15 +65,29 @@ fn log_with_level(level: LogLevel, message: &str) {
     }
 }
 
+/// Stable ID for a result: a 64-bit FNV-1a hash of `content`, which unlike
+/// `DefaultHasher` is the same across Rust releases and platforms. IDs key the usage
+/// history, so changing this scheme needs a migration.
 pub fn generate_id(prefix: &str, content: &str) -> String {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let hash = content.bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    });
+
+    format!("{}_{:016x}", prefix, hash)
+}
+
+/// The ID scheme used before `generate_id` was stable. Only for recognizing old keys
+/// in the usage history; the result depends on the toolchain that built the binary.
+pub fn legacy_generate_id(prefix: &str, content: &str) -> String {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
 
     let mut hasher = DefaultHasher::new();
     content.hash(&mut hasher);
-    let hash = hasher.finish();
-
-    format!("{}_{:x}", prefix, hash)
+    format!("{}_{:x}", prefix, hasher.finish())
 }
 
 pub fn truncate_text(text: &str, max_length: usize) -> String {
@@ -85,6 +99,105 @@ pub fn truncate_text(text: &str, max_length: usize) -> String {
     }
 }
 
+/// Quote a single argument for `sh`, leaving plain words readable
+pub fn shell_quote(arg: &str) -> String {
+    let is_plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+    if is_plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Join an argv into a command line that `sh` splits back into the same arguments
+pub fn shell_join<S: AsRef<str>>(argv: &[S]) -> String {
+    argv.iter().map(|arg| shell_quote(arg.as_ref())).collect::<Vec<_>>().join(" ")
+}
+
+/// Split typed arguments the way `sh` would, without running any expansions
+/// except a leading `~`. Single quotes are literal, double quotes allow `\"`,
+/// `\\`, `\$` and `` \` `` escapes, and an unterminated quote runs to the end.
+pub fn shell_split(input: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    // Only a `~` typed unquoted at the start of an argument means home
+    let mut expand_tilde = false;
+    let mut chars = input.chars().peekable();
+
+    let mut finish = |current: &mut String, expand_tilde: bool| {
+        let arg = std::mem::take(current);
+        args.push(if expand_tilde { shellexpand::tilde(&arg).to_string() } else { arg });
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_arg {
+                    finish(&mut current, expand_tilde);
+                    in_arg = false;
+                }
+                continue;
+            }
+            '\'' => {
+                for c in chars.by_ref() {
+                    if c == '\'' {
+                        break;
+                    }
+                    current.push(c);
+                }
+            }
+            '"' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' if chars.peek().is_some_and(|next| "\"\\$`".contains(*next)) => {
+                            current.extend(chars.next());
+                        }
+                        _ => current.push(c),
+                    }
+                }
+            }
+            '\\' => current.push(chars.next().unwrap_or('\\')),
+            _ => current.push(c),
+        }
+        if !in_arg {
+            expand_tilde = c == '~';
+        }
+        in_arg = true;
+    }
+    if in_arg {
+        finish(&mut current, expand_tilde);
+    }
+
+    args
+}
+
+/// Resolve a program name the way the shell would: paths are checked directly,
+/// bare names are looked up in `PATH`
+pub fn find_executable(program: &str) -> Option<std::path::PathBuf> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let is_executable = |path: &std::path::Path| {
+        path.metadata()
+            .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+            .unwrap_or(false)
+    };
+
+    if program.contains('/') {
+        let path = std::path::PathBuf::from(shellexpand::tilde(program).as_ref());
+        return is_executable(&path).then_some(path);
+    }
+
+    let path_var = std::env::var_os("PATH")?;
+    std::env::split_paths(&path_var)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+
 pub fn fuzzy_match(text: &str, pattern: &str) -> bool {
     let text_chars: Vec<char> = text.to_lowercase().chars().collect();
     let pattern_chars: Vec<char> = pattern.to_lowercase().chars().collect();
@@ -110,47 +223,76 @@ pub fn fuzzy_match(text: &str, pattern: &str) -> bool {
     pattern_idx == pattern_chars.len()
 }
 
-pub fn calculate_relevance_score(
-    query: &str,
-    title: &str,
-    description: &str,
-    categories: &[String],
-) -> i32 {
+/// How a query matched an item, from strongest to weakest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    Exact,
+    TitlePrefix,
+    TitleContains,
+    Description,
+    Category,
+    Fuzzy,
+}
+
+impl MatchKind {
+    /// Normalized relevance for providers to report
+    pub fn relevance(self) -> f32 {
+        match self {
+            MatchKind::Exact => 1.0,
+            MatchKind::TitlePrefix => 0.8,
+            MatchKind::TitleContains => 0.6,
+            MatchKind::Description => 0.4,
+            MatchKind::Category => 0.3,
+            MatchKind::Fuzzy => 0.2,
+        }
+    }
+}
+
+pub fn match_kind(query: &str, title: &str, description: &str, categories: &[String]) -> Option<MatchKind> {
     let query_lower = query.to_lowercase();
     let title_lower = title.to_lowercase();
     let description_lower = description.to_lowercase();
 
-    let mut score = 0;
-
-    // Exact title match gets highest score
     if title_lower == query_lower {
-        score += 1000;
-    }
-    // Title starts with query
-    else if title_lower.starts_with(&query_lower) {
-        score += 500;
-    }
-    // Title contains query
-    else if title_lower.contains(&query_lower) {
-        score += 200;
-    }
-    // Description contains query
-    else if description_lower.contains(&query_lower) {
-        score += 100;
-    }
-    // Category contains query
-    else if categories
+        Some(MatchKind::Exact)
+    } else if title_lower.starts_with(&query_lower) {
+        Some(MatchKind::TitlePrefix)
+    } else if title_lower.contains(&query_lower) {
+        Some(MatchKind::TitleContains)
+    } else if description_lower.contains(&query_lower) {
+        Some(MatchKind::Description)
+    } else if categories
         .iter()
         .any(|c| c.to_lowercase().contains(&query_lower))
     {
-        score += 50;
-    }
-    // Fuzzy match as last resort
-    else if fuzzy_match(&title_lower, &query_lower) {
-        score += 25;
+        Some(MatchKind::Category)
+    } else if fuzzy_match(&title_lower, &query_lower) {
+        Some(MatchKind::Fuzzy)
+    } else {
+        None
     }
+}
 
-    score
+/// Relevance of an item for a query between 0 and 1; 0 means no match
+pub fn relevance(query: &str, title: &str, description: &str, categories: &[String]) -> f32 {
+    match_kind(query, title, description, categories).map_or(0.0, MatchKind::relevance)
+}
+
+pub fn calculate_relevance_score(
+    query: &str,
+    title: &str,
+    description: &str,
+    categories: &[String],
+) -> i32 {
+    match match_kind(query, title, description, categories) {
+        Some(MatchKind::Exact) => 1000,
+        Some(MatchKind::TitlePrefix) => 500,
+        Some(MatchKind::TitleContains) => 200,
+        Some(MatchKind::Description) => 100,
+        Some(MatchKind::Category) => 50,
+        Some(MatchKind::Fuzzy) => 25,
+        None => 0,
+    }
 }
 
 #[cfg(test)]
@@ -158,6 +300,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_fuzzy_match() {
         assert_eq!(fuzzy_match("firefox", "fire"), true);
         assert_eq!(fuzzy_match("firefox", "fox"), true);
@@ -189,10 +332,70 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_generate_id_is_stable() {
+        // Published FNV-1a test vectors; these must never change
+        assert_eq!(generate_id("dir", ""), "dir_cbf29ce484222325");
+        assert_eq!(generate_id("dir", "a"), "dir_af63dc4c8601ec8c");
+        assert_ne!(generate_id("dir", "/home"), generate_id("dir", "/tmp"));
+    }
+
     #[test]
     fn test_truncate_text() {
         assert_eq!(truncate_text("short", 10), "short");
         assert_eq!(truncate_text("this is a very long text", 10), "this is...");
         assert_eq!(truncate_text("12345678901", 10), "1234567...");
     }
+
+    #[test]
+    fn test_shell_join_round_trips_awkward_arguments() {
+        let argv = ["code", "--goto", "/tmp/it's a file.md", "", "$HOME"];
+        let line = shell_join(&argv);
+        assert_eq!(line, r#"code --goto '/tmp/it'\''s a file.md' '' '$HOME'"#);
+
+        let output = std::process::Command::new("sh")
+            .args(["-c", &format!("printf '%s\\n' {}", shell_join(&argv[1..]))])
+            .output()
+            .unwrap();
+        let lines: Vec<_> = String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect();
+        assert_eq!(lines, argv[1..]);
+    }
+
+    #[test]
+    fn test_shell_split_undoes_shell_join() {
+        let argv = ["--goto", "/tmp/it's a file.md", "", "$HOME", r#"say "hi""#];
+        assert_eq!(shell_split(&shell_join(&argv)), argv);
+
+        let home = shellexpand::tilde("~").to_string();
+        assert_eq!(
+            shell_split(r#"  -n "two words" say\ hi ~/notes '~/literal' a~b"#),
+            vec![
+                "-n".to_string(),
+                "two words".to_string(),
+                "say hi".to_string(),
+                format!("{}/notes", home),
+                "~/literal".to_string(),
+                "a~b".to_string(),
+            ]
+        );
+        assert_eq!(shell_split(r#"unterminated "quote here"#), vec!["unterminated", "quote here"]);
+        assert!(shell_split("   ").is_empty());
+    }
+
+    #[test]
+    fn test_find_executable() {
+        assert!(find_executable("sh").is_some());
+        assert_eq!(find_executable("/bin/sh"), Some(std::path::PathBuf::from("/bin/sh")));
+        assert!(find_executable("wayfindr-no-such-program").is_none());
+
+        // Plain files aren't programs until they're marked executable
+        use std::os::unix::fs::PermissionsExt;
+        let temp = tempfile::tempdir().unwrap();
+        let script = temp.path().join("script");
+        std::fs::write(&script, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(find_executable(script.to_str().unwrap()).is_none());
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(find_executable(script.to_str().unwrap()), Some(script.clone()));
+    }
 }